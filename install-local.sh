@@ -2,6 +2,7 @@
 set -euo pipefail
 cargo build --release
 install -Dm755 ./target/release/spliterma "$HOME/.local/bin/spliterma"
+install -Dm755 ./target/release/spliterma-ctl "$HOME/.local/bin/spliterma-ctl"
 install -Dm644 data/com.spliterma.app.desktop "$HOME/.local/share/applications/com.spliterma.app.desktop"
 install -Dm644 data/com.spliterma.app.metainfo.xml "$HOME/.local/share/metainfo/com.spliterma.app.metainfo.xml"
 for dir in data/icons/hicolor/*/apps; do
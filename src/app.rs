@@ -1,44 +1,374 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+
 use gtk::gio;
 use gtk4 as gtk;
 use vte4::prelude::*;
 
-use crate::ui::{menus::setup_global_menu, terminal::create_terminal_with_title};
+use crate::cli::{CliOptions, USAGE, parse_args};
+use crate::config::{self, Config, keybindings, watch};
+use crate::layout::persist::read_layout_file;
+use crate::layout::session::{load_session, save_session};
+use crate::layout::tree::{Direction, LayoutTree, PaneId};
+use crate::layout::types::{SavedLayout, SplitType, TerminalLayout, WindowLayout};
+use crate::remote;
+use crate::ui::broadcast::setup_broadcast_actions;
+use crate::ui::menus::setup_global_menu;
+use crate::ui::search::toggle_search;
+use crate::ui::split::{next_pane_layout, split_terminal, stop_terminal};
+use crate::ui::tabs::{
+    add_tab, build_notebook, current_workspace, setup_tab_actions, single_tab, window_notebook,
+    workspaces,
+};
+use crate::ui::terminal::{
+    apply_terminal_config, clear_scrollback, find_terminal_in_container, hang_up, zoom_terminal,
+};
+use crate::ui::theme;
+use crate::ui::workspace::{Workspace, orientation_of, pane_container_of, pane_id_of};
+use crate::util::errors::show_error_dialog;
 
 pub fn run() {
+    // Validate locally so usage errors and --help reach the calling terminal,
+    // even when an already running instance ends up handling the command line
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match parse_args(&args) {
+        Ok(options) if options.help => {
+            print!("{USAGE}");
+            return;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("spliterma: {e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    }
+
     let app = gtk::Application::builder()
         .application_id("com.spliterma.app")
+        .flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE)
         .build();
 
-    app.connect_activate(|app| {
-        // Prefer dark theme globally
-        if let Some(settings) = gtk::Settings::default() {
-            settings.set_gtk_application_prefer_dark_theme(true);
+    app.connect_startup(|app| {
+        if let Err(e) = config::init() {
+            eprintln!("Ignoring config: {e}");
+            // Shown once the first window is up
+            glib::idle_add_local_once(move || show_error_dialog("Invalid configuration", &e));
+        }
+        theme::init();
+        setup_app_actions(app);
+        keybindings::apply(app);
+        start_autosave(app);
+        watch::watch(app, reload_config);
+        remote::export(app);
+    });
+
+    app.connect_activate(open_startup_windows);
+
+    app.connect_command_line(|app, cmdline| {
+        let args: Vec<String> = cmdline
+            .arguments()
+            .iter()
+            .skip(1)
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+
+        let result =
+            parse_args(&args).and_then(|options| open_from_options(app, options, cmdline.cwd()));
+        match result {
+            Ok(()) => glib::ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("spliterma: {e}");
+                // Without a window nobody would see the dialog
+                if app.active_window().is_some() {
+                    show_error_dialog("Could not open workspace", &e);
+                }
+                glib::ExitCode::FAILURE
+            }
+        }
+    });
+
+    app.run();
+}
+
+fn open_from_options(
+    app: &gtk::Application,
+    options: CliOptions,
+    cwd: Option<PathBuf>,
+) -> Result<(), String> {
+    // Relative paths are relative to wherever spliterma was invoked
+    let resolve = |path: PathBuf| match &cwd {
+        Some(cwd) if path.is_relative() => cwd.join(path),
+        _ => path,
+    };
+
+    if let Some(path) = options.layout.clone() {
+        let saved = read_layout_file(&resolve(path))?;
+        return open_saved_windows(app, &saved);
+    }
+
+    let working_dir = options.working_directory.clone().map(resolve).or(cwd);
+    let active = app
+        .active_window()
+        .and_then(|w| w.downcast::<gtk::ApplicationWindow>().ok());
+
+    match (active, options.split) {
+        _ if options == CliOptions::default() => {
+            open_startup_windows(app);
+            Ok(())
+        }
+        (Some(window), Some(split)) if !options.new_window => {
+            split_focused_pane(&window, split, options, working_dir)
+        }
+        // Panes asked for from inside a running instance open as a new tab
+        (Some(window), None) if !options.new_window => {
+            let notebook = window_notebook(&window).ok_or("Window has no tabs")?;
+            let pane = pane_from_options(next_pane_layout(), options, working_dir);
+            let index = add_tab(&notebook, &single_pane(pane), None);
+            notebook.set_current_page(Some(index));
+            window.present();
+            Ok(())
+        }
+        _ => {
+            let pane = pane_from_options(next_pane_layout(), options, working_dir);
+            open_window(app, &single_tab(&single_pane(pane)), None);
+            Ok(())
+        }
+    }
+}
+
+// Apply the pane settings given on the command line
+fn pane_from_options(
+    mut pane: TerminalLayout,
+    options: CliOptions,
+    working_dir: Option<PathBuf>,
+) -> TerminalLayout {
+    if let Some(title) = options.title {
+        pane.name = title;
+    }
+    if let Some(dir) = working_dir {
+        pane.working_dir = dir.to_string_lossy().into_owned();
+    }
+    pane.command = options.command;
+    pane
+}
+
+// Plain `spliterma` with no window open yet picks up where the last session left off
+fn open_startup_windows(app: &gtk::Application) {
+    if app.windows().is_empty() && config::get().session.enabled {
+        let restored = load_session().and_then(|saved| match saved {
+            Some(saved) => open_saved_windows(app, &saved),
+            None => Ok(()),
+        });
+        if let Err(e) = restored {
+            eprintln!("Could not restore session, starting fresh: {e}");
+        }
+        if !app.windows().is_empty() {
+            return;
         }
+    }
+
+    open_window(app, &single_tab(&single_pane(next_pane_layout())), None);
+}
+
+/// Open one window per saved window, stopping at the first that can't be built.
+pub(crate) fn open_saved_windows(
+    app: &gtk::Application,
+    saved: &SavedLayout,
+) -> Result<(), String> {
+    let windows = saved.windows();
+    if windows.is_empty() {
+        return Err("Layout has no windows".into());
+    }
+    for layout in &windows {
+        open_saved_window(app, layout)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn open_saved_window(
+    app: &gtk::Application,
+    layout: &WindowLayout,
+) -> Result<gtk::ApplicationWindow, String> {
+    let notebook = build_notebook(layout)?;
+    Ok(open_window(app, &notebook, Some(layout)))
+}
+
+fn single_pane(pane: TerminalLayout) -> Workspace {
+    let (tree, _) = LayoutTree::with_pane(pane);
+    Workspace::new(tree)
+}
 
-        let window = gtk::ApplicationWindow::builder()
-            .application(app)
-            .title("Spliterma")
-            .default_width(1000)
-            .default_height(700)
-            .build();
+fn split_focused_pane(
+    window: &gtk::ApplicationWindow,
+    split: SplitType,
+    options: CliOptions,
+    working_dir: Option<PathBuf>,
+) -> Result<(), String> {
+    let container = find_focused_terminal(window)
+        .and_then(|t| pane_container_of(&t))
+        .ok_or("No pane to split")?;
+    let workspace = Workspace::for_widget(&container).ok_or("No pane to split")?;
+    let id = pane_id_of(&container).ok_or("No pane to split")?;
 
-        // Set up application-level copy/paste actions BEFORE creating terminals
-        setup_copy_paste_actions(app, &window);
+    let pane = pane_from_options(next_pane_layout(), options, working_dir);
+    workspace.split(id, orientation_of(split), pane);
+    window.present();
+    Ok(())
+}
+
+fn open_window(
+    app: &gtk::Application,
+    notebook: &gtk::Notebook,
+    geometry: Option<&WindowLayout>,
+) -> gtk::ApplicationWindow {
+    let config = config::get();
+    apply_dark_theme(&config);
+
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .title("Spliterma")
+        .default_width(config.window.width)
+        .default_height(config.window.height)
+        .build();
 
-        // טרמינל פתיחה
-        let initial = create_terminal_with_title("Terminal 1", 0, None);
+    // Copy/paste and pane management act on the focused terminal of this window
+    setup_copy_paste_actions(&window);
+    setup_pane_actions(&window);
 
-        // תפריט עליון + קיצורי מקלדת
-        setup_global_menu(&window);
+    // תפריט עליון + קיצורי מקלדת
+    setup_global_menu(&window);
+    setup_tab_actions(&window);
+    setup_broadcast_actions(&window);
 
-        window.set_child(Some(&initial));
-        window.present();
+    window.connect_close_request(|window| {
+        if config::get().session.enabled {
+            if let Some(app) = window.application() {
+                if let Err(e) = save_session(&app, Some(window)) {
+                    eprintln!("Saving session failed: {e}");
+                }
+            }
+        }
+        release_window(window);
+        glib::Propagation::Proceed
     });
 
-    app.run();
+    window.set_child(Some(notebook));
+    if let Some(geometry) = geometry {
+        apply_geometry(&window, geometry);
+    }
+    window.present();
+    window
 }
 
-fn setup_copy_paste_actions(app: &gtk::Application, window: &gtk::ApplicationWindow) {
+// The app keeps running while other windows are open, so end this window's
+// shells and let go of its panes now
+fn release_window(window: &gtk::ApplicationWindow) {
+    let Some(notebook) = window_notebook(window) else {
+        return;
+    };
+    for workspace in workspaces(&notebook) {
+        for container in workspace.containers() {
+            if let Some(terminal) = find_terminal_in_container(&container) {
+                hang_up(&terminal);
+            }
+        }
+        workspace.release();
+    }
+}
+
+/// Size and maximized/fullscreen state of a saved window.
+pub(crate) fn apply_geometry(window: &gtk::ApplicationWindow, layout: &WindowLayout) {
+    if let Some([width, height]) = layout.size {
+        window.set_default_size(width, height);
+    }
+    window.set_maximized(layout.maximized);
+    window.set_fullscreened(layout.fullscreen);
+}
+
+fn setup_app_actions(app: &gtk::Application) {
+    let new_window = gio::SimpleAction::new("new-window", None);
+    let app_weak = app.downgrade();
+    new_window.connect_activate(move |_, _| {
+        if let Some(app) = app_weak.upgrade() {
+            open_window(&app, &single_tab(&single_pane(next_pane_layout())), None);
+        }
+    });
+    app.add_action(&new_window);
+}
+
+fn apply_dark_theme(config: &Config) {
+    // Prefer dark theme globally
+    if let Some(settings) = gtk::Settings::default() {
+        settings.set_gtk_application_prefer_dark_theme(config.window.dark_theme);
+    }
+}
+
+// config.toml or keybindings.toml changed on disk
+fn reload_config(app: &gtk::Application) {
+    keybindings::apply(app);
+    theme::load_user_css();
+
+    let config = match config::reload() {
+        Ok(config) => config,
+        Err(e) => {
+            show_error_dialog("Invalid configuration", &e);
+            return;
+        }
+    };
+
+    apply_dark_theme(&config);
+    for window in app.windows() {
+        let Some(notebook) = window
+            .downcast::<gtk::ApplicationWindow>()
+            .ok()
+            .and_then(|w| window_notebook(&w))
+        else {
+            continue;
+        };
+        for workspace in workspaces(&notebook) {
+            for container in workspace.containers() {
+                if let Some(terminal) = find_terminal_in_container(&container) {
+                    apply_terminal_config(&terminal, &config);
+                }
+            }
+        }
+    }
+    start_autosave(app);
+    println!("Configuration reloaded");
+}
+
+thread_local! {
+    static AUTOSAVE: RefCell<Option<glib::SourceId>> = const { RefCell::new(None) };
+}
+
+// Save the session in the background too, so a crash or logout loses little
+fn start_autosave(app: &gtk::Application) {
+    // Restarted when the config is reloaded
+    if let Some(source) = AUTOSAVE.with(|a| a.borrow_mut().take()) {
+        source.remove();
+    }
+
+    let session = config::get().session.clone();
+    if !session.enabled || session.autosave_interval == 0 {
+        return;
+    }
+
+    let app = app.downgrade();
+    let source = glib::timeout_add_seconds_local(session.autosave_interval, move || {
+        let Some(app) = app.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        if !app.windows().is_empty() {
+            if let Err(e) = save_session(&app, None) {
+                eprintln!("Saving session failed: {e}");
+            }
+        }
+        glib::ControlFlow::Continue
+    });
+    AUTOSAVE.with(|a| *a.borrow_mut() = Some(source));
+}
+
+fn setup_copy_paste_actions(window: &gtk::ApplicationWindow) {
     // Copy action
     let copy_action = gio::SimpleAction::new("copy", None);
     let window_weak = window.downgrade();
@@ -69,16 +399,107 @@ fn setup_copy_paste_actions(app: &gtk::Application, window: &gtk::ApplicationWin
         }
     });
 
-    // Add actions to application
-    app.add_action(&copy_action);
-    app.add_action(&paste_action);
+    // Add actions to the window
+    window.add_action(&copy_action);
+    window.add_action(&paste_action);
+}
+
+// Share of the split moved by one resize step
+const RESIZE_STEP: f64 = 0.05;
 
-    // Set keyboard accelerators - this is the key part for Flatpak!
-    app.set_accels_for_action("app.copy", &["<Ctrl><Shift>c"]);
-    app.set_accels_for_action("app.paste", &["<Ctrl><Shift>v"]);
+fn setup_pane_actions(window: &gtk::ApplicationWindow) {
+    add_pane_action(window, "split-horizontal", |_, container| {
+        split_terminal(container, gtk::Orientation::Horizontal)
+    });
+    add_pane_action(window, "split-vertical", |_, container| {
+        split_terminal(container, gtk::Orientation::Vertical)
+    });
+    add_pane_action(window, "close-pane", stop_terminal);
+    add_pane_action(window, "find", |_, container| toggle_search(container));
+    add_pane_action(window, "clear-scrollback", |terminal, _| {
+        clear_scrollback(terminal)
+    });
+    add_pane_action(window, "focus-next", |_, container| {
+        with_workspace(container, |workspace, id| workspace.focus_cycle(id, 1))
+    });
+    add_pane_action(window, "focus-previous", |_, container| {
+        with_workspace(container, |workspace, id| workspace.focus_cycle(id, -1))
+    });
+
+    for (side, direction) in [
+        ("left", Direction::Left),
+        ("right", Direction::Right),
+        ("up", Direction::Up),
+        ("down", Direction::Down),
+    ] {
+        add_pane_action(window, &format!("focus-{side}"), move |_, container| {
+            with_workspace(container, |workspace, id| {
+                workspace.focus_neighbour(id, direction);
+            })
+        });
+        add_pane_action(window, &format!("swap-{side}"), move |_, container| {
+            with_workspace(container, |workspace, id| {
+                workspace.swap_with_neighbour(id, direction);
+            })
+        });
+    }
+
+    for (name, orientation, delta) in [
+        ("resize-wider", gtk::Orientation::Horizontal, RESIZE_STEP),
+        (
+            "resize-narrower",
+            gtk::Orientation::Horizontal,
+            -RESIZE_STEP,
+        ),
+        ("resize-taller", gtk::Orientation::Vertical, RESIZE_STEP),
+        ("resize-shorter", gtk::Orientation::Vertical, -RESIZE_STEP),
+    ] {
+        add_pane_action(window, name, move |_, container| {
+            with_workspace(container, |workspace, id| {
+                workspace.resize(id, orientation, delta);
+            })
+        });
+    }
+
+    for (name, zoom_in) in [
+        ("zoom-in", Some(true)),
+        ("zoom-out", Some(false)),
+        ("zoom-reset", None),
+    ] {
+        add_pane_action(window, name, move |terminal, _| {
+            zoom_terminal(terminal, zoom_in)
+        });
+    }
+}
+
+// Window action that runs on the pane holding the focused terminal
+fn add_pane_action(
+    window: &gtk::ApplicationWindow,
+    name: &str,
+    handler: impl Fn(&vte4::Terminal, &gtk::Box) + 'static,
+) {
+    let action = gio::SimpleAction::new(name, None);
+    let window_weak = window.downgrade();
+    action.connect_activate(move |_, _| {
+        let Some(window) = window_weak.upgrade() else {
+            return;
+        };
+        if let Some(terminal) = find_focused_terminal(&window) {
+            if let Some(container) = pane_container_of(&terminal) {
+                handler(&terminal, &container);
+            }
+        }
+    });
+    window.add_action(&action);
+}
+
+fn with_workspace(container: &gtk::Box, f: impl FnOnce(&Workspace, PaneId)) {
+    if let (Some(workspace), Some(id)) = (Workspace::for_widget(container), pane_id_of(container)) {
+        f(&workspace, id);
+    }
 }
 
-fn find_focused_terminal(window: &gtk::ApplicationWindow) -> Option<vte4::Terminal> {
+pub(crate) fn find_focused_terminal(window: &gtk::ApplicationWindow) -> Option<vte4::Terminal> {
     // Method 1: Try to get the currently focused widget
     if let Some(focus_widget) = gtk::prelude::RootExt::focus(window) {
         // Check if it's a terminal directly
@@ -96,9 +517,9 @@ fn find_focused_terminal(window: &gtk::ApplicationWindow) -> Option<vte4::Termin
         }
     }
 
-    // Method 2: Search through all widgets to find terminals
-    if let Some(root_child) = window.child() {
-        if let Some(terminal) = find_terminal_recursive(&root_child) {
+    // Method 2: Search through the widgets of the visible tab
+    if let Some(workspace) = current_workspace(window) {
+        if let Some(terminal) = find_terminal_recursive(workspace.widget().upcast_ref()) {
             return Some(terminal);
         }
     }
@@ -0,0 +1,407 @@
+use std::io::Read;
+use std::path::Path;
+
+use gio::prelude::*;
+use serde::Serialize;
+
+use spliterma::layout::types::TerminalLayout;
+use spliterma::remote::{BUS_NAME, INTERFACE, OBJECT_PATH, PANE_ID_ENV};
+
+const USAGE: &str = "\
+Usage: spliterma-ctl COMMAND [OPTIONS]
+
+Commands:
+  list [--json]                          List the panes of the running Spliterma
+  split [--pane ID] [--vertical] [-- COMMAND [ARGS...]]
+                                         Split a pane, print the new pane's id
+  send-text [--pane ID] [TEXT...]        Type TEXT (or stdin) into a pane
+  focus [--pane ID]                      Focus a pane and raise its window
+  rename [--pane ID] NAME                Rename a pane
+  color [--pane ID] #RRGGBB              Change a pane's title bar colour
+  close [--pane ID]                      Close a pane
+  save-layout FILE                       Save the active window's layout
+  load-layout FILE                       Load a layout into the active window
+
+ID is a pane id from `list` or a pane title. Without --pane, commands run inside a
+pane act on that pane ($SPLITERMA_PANE_ID), others on the focused pane.
+";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    List {
+        json: bool,
+    },
+    Split {
+        pane: String,
+        vertical: bool,
+        command: Vec<String>,
+    },
+    // No text means read it from stdin
+    SendText {
+        pane: String,
+        text: Option<String>,
+    },
+    Focus {
+        pane: String,
+    },
+    Rename {
+        pane: String,
+        name: String,
+    },
+    SetColor {
+        pane: String,
+        color: String,
+    },
+    Close {
+        pane: String,
+    },
+    SaveLayout {
+        path: String,
+    },
+    LoadLayout {
+        path: String,
+    },
+    Help,
+}
+
+// `list --json` prints saved-layout panes (with their ids) plus where they are running
+#[derive(Serialize)]
+struct ListedPane {
+    pid: i32,
+    window: u32,
+    tab: u32,
+    #[serde(flatten)]
+    pane: TerminalLayout,
+}
+
+/// `default_pane` is used without --pane; "" lets Spliterma pick the focused pane.
+fn parse_args(args: &[String], default_pane: &str) -> Result<Command, String> {
+    let Some((name, rest)) = args.split_first() else {
+        return Err("missing command".into());
+    };
+    if matches!(name.as_str(), "-h" | "--help" | "help") {
+        return Ok(Command::Help);
+    }
+
+    let mut pane = default_pane.to_string();
+    let mut vertical = false;
+    let mut json = false;
+    let mut words = Vec::new();
+    let mut command = Vec::new();
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        // `--pane=ID` works like `--pane ID`, as in spliterma itself
+        if let Some(value) = arg.strip_prefix("--pane=") {
+            pane = value.to_string();
+            continue;
+        }
+        match arg.as_str() {
+            "-p" | "--pane" => pane = iter.next().ok_or("--pane needs a value")?.clone(),
+            "-v" | "--vertical" if name == "split" => vertical = true,
+            "--horizontal" if name == "split" => vertical = false,
+            "--json" if name == "list" => json = true,
+            "--" => {
+                command = iter.by_ref().cloned().collect();
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+            _ => words.push(arg.clone()),
+        }
+    }
+
+    let one = |what: &str| match words.as_slice() {
+        [word] => Ok(word.clone()),
+        _ => Err(format!("{name} needs exactly one {what}")),
+    };
+    let none = || {
+        if words.is_empty() && command.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{name} takes no arguments"))
+        }
+    };
+    let command = match name.as_str() {
+        "list" => none().map(|_| Command::List { json })?,
+        "split" => {
+            if !words.is_empty() {
+                return Err("put the command after --, e.g. split -- htop".into());
+            }
+            Command::Split {
+                pane,
+                vertical,
+                command,
+            }
+        }
+        "send-text" => {
+            // `--` lets the text start with a dash
+            words.extend(command);
+            let text = Some(words.join(" ")).filter(|t| !t.is_empty());
+            Command::SendText { pane, text }
+        }
+        "focus" => none().map(|_| Command::Focus { pane })?,
+        "rename" => Command::Rename {
+            name: one("name")?,
+            pane,
+        },
+        "color" => Command::SetColor {
+            color: one("colour")?,
+            pane,
+        },
+        "close" => none().map(|_| Command::Close { pane })?,
+        "save-layout" => Command::SaveLayout {
+            path: absolute(&one("file")?)?,
+        },
+        "load-layout" => Command::LoadLayout {
+            path: absolute(&one("file")?)?,
+        },
+        other => return Err(format!("unknown command '{other}'")),
+    };
+    Ok(command)
+}
+
+// Spliterma resolves paths against its own directory, not ours
+fn absolute(path: &str) -> Result<String, String> {
+    std::path::absolute(Path::new(path))
+        .map(|p| p.to_string_lossy().into_owned())
+        .map_err(|e| format!("{path}: {e}"))
+}
+
+fn call(
+    connection: &gio::DBusConnection,
+    method: &str,
+    args: glib::Variant,
+) -> Result<glib::Variant, String> {
+    connection
+        .call_sync(
+            Some(BUS_NAME),
+            OBJECT_PATH,
+            INTERFACE,
+            method,
+            Some(&args),
+            None,
+            gio::DBusCallFlags::NO_AUTO_START,
+            -1,
+            gio::Cancellable::NONE,
+        )
+        .map_err(|mut e| {
+            if e.matches(gio::DBusError::ServiceUnknown) {
+                "Spliterma is not running".to_string()
+            } else {
+                // Drop the "GDBus.Error:name: " prefix, the message is enough
+                gio::DBusError::strip_remote_error(&mut e);
+                e.message().to_string()
+            }
+        })
+}
+
+fn run(command: Command) -> Result<(), String> {
+    // Help works without a session bus
+    if command == Command::Help {
+        print!("{USAGE}");
+        return Ok(());
+    }
+    let connection = gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)
+        .map_err(|e| format!("No session bus: {e}"))?;
+
+    match command {
+        // Printed above
+        Command::Help => {}
+        Command::List { json } => {
+            let reply = call(&connection, "ListPanes", ().to_variant())?;
+            let (panes,) = reply
+                .get::<(Vec<(String, String, String, String, i32, u32, u32)>,)>()
+                .ok_or("Unexpected reply from Spliterma")?;
+            let panes: Vec<ListedPane> = panes
+                .into_iter()
+                .map(
+                    |(id, name, working_dir, color, pid, window, tab)| ListedPane {
+                        pid,
+                        window,
+                        tab,
+                        pane: TerminalLayout {
+                            id: Some(id),
+                            name,
+                            working_dir,
+                            color: Some(color).filter(|c| !c.is_empty()),
+                            ..Default::default()
+                        },
+                    },
+                )
+                .collect();
+            if json {
+                let json = serde_json::to_string_pretty(&panes).map_err(|e| e.to_string())?;
+                println!("{json}");
+            } else {
+                for pane in &panes {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        pane.pane.id.as_deref().unwrap_or_default(),
+                        pane.pane.name,
+                        pane.pid,
+                        pane.pane.working_dir
+                    );
+                }
+            }
+        }
+        Command::Split {
+            pane,
+            vertical,
+            command,
+        } => {
+            let reply = call(&connection, "Split", (pane, vertical, command).to_variant())?;
+            if let Some((id,)) = reply.get::<(String,)>() {
+                println!("{id}");
+            }
+        }
+        Command::SendText { pane, text } => {
+            let text = match text {
+                Some(text) => text,
+                None => {
+                    let mut text = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut text)
+                        .map_err(|e| format!("Reading stdin failed: {e}"))?;
+                    text
+                }
+            };
+            call(&connection, "SendText", (pane, text).to_variant())?;
+        }
+        Command::Focus { pane } => {
+            call(&connection, "Focus", (pane,).to_variant())?;
+        }
+        Command::Rename { pane, name } => {
+            call(&connection, "Rename", (pane, name).to_variant())?;
+        }
+        Command::SetColor { pane, color } => {
+            call(&connection, "SetColor", (pane, color).to_variant())?;
+        }
+        Command::Close { pane } => {
+            call(&connection, "Close", (pane,).to_variant())?;
+        }
+        Command::SaveLayout { path } => {
+            call(&connection, "SaveLayout", (path,).to_variant())?;
+        }
+        Command::LoadLayout { path } => {
+            call(&connection, "LoadLayout", (path,).to_variant())?;
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // Inside a pane, commands act on that pane rather than the focused one
+    let own_pane = std::env::var(PANE_ID_ENV).unwrap_or_default();
+    let command = match parse_args(&args, &own_pane) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("spliterma-ctl: {e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = run(command) {
+        eprintln!("spliterma-ctl: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>(), "")
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(
+            parse(&[
+                "split",
+                "--pane",
+                "3",
+                "--vertical",
+                "--",
+                "htop",
+                "-d",
+                "5"
+            ]),
+            Ok(Command::Split {
+                pane: "3".into(),
+                vertical: true,
+                command: vec!["htop".into(), "-d".into(), "5".into()],
+            })
+        );
+        assert_eq!(
+            parse(&["send-text", "-p", "api", "make", "test"]),
+            Ok(Command::SendText {
+                pane: "api".into(),
+                text: Some("make test".into()),
+            })
+        );
+        assert_eq!(
+            parse(&["send-text"]),
+            Ok(Command::SendText {
+                pane: String::new(),
+                text: None,
+            })
+        );
+        assert_eq!(parse(&["list", "--json"]), Ok(Command::List { json: true }));
+        let args = ["close".to_string()];
+        assert_eq!(
+            parse_args(&args, "0b5c3f3e-5d1a-4c1e-9f57-1f1c2a6c8d2e"),
+            Ok(Command::Close {
+                pane: "0b5c3f3e-5d1a-4c1e-9f57-1f1c2a6c8d2e".into()
+            })
+        );
+        assert!(matches!(
+            parse(&["save-layout", "/tmp/a.json"]),
+            Ok(Command::SaveLayout { path }) if path == "/tmp/a.json"
+        ));
+    }
+
+    #[test]
+    fn pane_with_equals() {
+        assert_eq!(
+            parse(&["focus", "--pane=api"]),
+            Ok(Command::Focus { pane: "api".into() })
+        );
+        assert_eq!(
+            parse(&["rename", "--pane=", "db"]),
+            Ok(Command::Rename {
+                pane: String::new(),
+                name: "db".into()
+            })
+        );
+    }
+
+    #[test]
+    fn usage_errors() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["explode"]).is_err());
+        assert!(parse(&["list", "--vertical"]).is_err());
+        assert!(parse(&["rename"]).is_err());
+        assert!(parse(&["split", "htop"]).is_err());
+        assert!(parse(&["close", "--pane"]).is_err());
+    }
+
+    #[test]
+    fn json_uses_the_layout_fields() {
+        let pane = ListedPane {
+            pid: 4242,
+            window: 0,
+            tab: 1,
+            pane: TerminalLayout {
+                id: Some("0b5c3f3e-5d1a-4c1e-9f57-1f1c2a6c8d2e".into()),
+                name: "API".into(),
+                working_dir: "/srv/api".into(),
+                ..Default::default()
+            },
+        };
+        let json = serde_json::to_value(&pane).unwrap();
+        assert_eq!(json["id"], "0b5c3f3e-5d1a-4c1e-9f57-1f1c2a6c8d2e");
+        assert_eq!(json["name"], "API");
+        assert_eq!(json["working_dir"], "/srv/api");
+        assert_eq!(json["split_type"], serde_json::Value::Null);
+    }
+}
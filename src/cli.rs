@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use crate::layout::types::SplitType;
+
+pub const USAGE: &str = "\
+Usage: spliterma [OPTIONS] [-e COMMAND [ARGS...]]
+
+Options:
+  -l, --layout FILE               Open a saved layout
+  -w, --working-directory DIR     Start the pane in DIR
+  -t, --title TITLE               Title of the pane
+  -s, --split h|v                 Open the pane as a split of the focused pane
+      --new-window                Open a new window instead of a tab in the running one
+  -e, --command COMMAND [ARGS...] Run COMMAND instead of the shell (must come last)
+  -h, --help                      Show this help
+";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CliOptions {
+    pub layout: Option<PathBuf>,
+    pub working_directory: Option<PathBuf>,
+    pub command: Option<Vec<String>>,
+    pub title: Option<String>,
+    pub split: Option<SplitType>,
+    pub new_window: bool,
+    pub help: bool,
+}
+
+/// Parse the arguments following the program name.
+pub fn parse_args(args: &[String]) -> Result<CliOptions, String> {
+    let mut options = CliOptions::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        // Accept both `--option value` and `--option=value`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = |name: &str| {
+            inline
+                .clone()
+                .or_else(|| iter.next().cloned())
+                .ok_or_else(|| format!("{name} needs a value"))
+        };
+
+        // Only long options get inline values; `--new-window=foo` is a mistake
+        let takes_value = matches!(
+            flag,
+            "--layout" | "--working-directory" | "--title" | "--split" | "--command"
+        );
+        if inline.is_some() && !takes_value {
+            return Err(format!("{flag} doesn't take a value"));
+        }
+
+        match flag {
+            "-h" | "--help" => options.help = true,
+            "--new-window" => options.new_window = true,
+            "-l" | "--layout" => options.layout = Some(value(flag)?.into()),
+            "-w" | "--working-directory" => options.working_directory = Some(value(flag)?.into()),
+            "-t" | "--title" => options.title = Some(value(flag)?),
+            "-s" | "--split" => {
+                options.split = Some(match value(flag)?.as_str() {
+                    "h" | "horizontal" => SplitType::Horizontal,
+                    "v" | "vertical" => SplitType::Vertical,
+                    other => return Err(format!("--split expects h or v, got '{other}'")),
+                })
+            }
+            "-e" | "--command" | "--" => {
+                // `--command=htop -d 5`: the inline value is the program
+                let command: Vec<String> =
+                    inline.into_iter().chain(iter.by_ref().cloned()).collect();
+                if command.is_empty() {
+                    return Err(format!("{flag} needs a command"));
+                }
+                options.command = Some(command);
+            }
+            other => return Err(format!("Unknown option '{other}'")),
+        }
+    }
+
+    if options.layout.is_some() && (options.command.is_some() || options.split.is_some()) {
+        return Err("--layout can't be combined with --command or --split".into());
+    }
+
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliOptions, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn no_arguments() {
+        assert_eq!(parse(&[]).unwrap(), CliOptions::default());
+    }
+
+    #[test]
+    fn options_and_command() {
+        let options = parse(&[
+            "--title",
+            "Logs",
+            "--working-directory=/var/log",
+            "-s",
+            "v",
+            "-e",
+            "tail",
+            "-f",
+            "syslog",
+        ])
+        .unwrap();
+
+        assert_eq!(options.title.as_deref(), Some("Logs"));
+        assert_eq!(options.working_directory, Some(PathBuf::from("/var/log")));
+        assert_eq!(options.split, Some(SplitType::Vertical));
+        // Everything after -e belongs to the command, even things that look like options
+        assert_eq!(
+            options.command,
+            Some(vec!["tail".into(), "-f".into(), "syslog".into()])
+        );
+    }
+
+    #[test]
+    fn layout_and_new_window() {
+        let options = parse(&["--new-window", "-l", "dev.json"]).unwrap();
+        assert!(options.new_window);
+        assert_eq!(options.layout, Some(PathBuf::from("dev.json")));
+    }
+
+    #[test]
+    fn inline_command_is_the_program() {
+        let options = parse(&["--command=htop", "-d", "5"]).unwrap();
+        assert_eq!(
+            options.command,
+            Some(vec!["htop".into(), "-d".into(), "5".into()])
+        );
+        assert_eq!(
+            parse(&["--command=htop"]).unwrap().command,
+            Some(vec!["htop".into()])
+        );
+    }
+
+    #[test]
+    fn flags_without_values_reject_inline_ones() {
+        assert!(parse(&["--new-window=foo"]).is_err());
+        assert!(parse(&["--help=foo"]).is_err());
+        assert!(parse(&["--=htop"]).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse(&["--layout"]).is_err());
+        assert!(parse(&["--split", "diagonal"]).is_err());
+        assert!(parse(&["-e"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+        assert!(parse(&["--layout", "a.json", "-e", "htop"]).is_err());
+    }
+}
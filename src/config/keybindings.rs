@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use gtk::prelude::*;
+use gtk4 as gtk;
+use serde::Deserialize;
+
+use crate::config::config_dir;
+use crate::util::errors::show_error_dialog;
+
+/// Every action that can be bound, with its default accelerators.
+pub const DEFAULT_BINDINGS: &[(&str, &[&str])] = &[
+    ("app.new-window", &["<Ctrl><Shift>n"]),
+    // Not Ctrl+S / Ctrl+O: vim and nano in the panes need them
+    ("win.save-layout", &["<Ctrl><Shift>s"]),
+    ("win.load-layout", &["<Ctrl><Shift>l"]),
+    ("win.copy", &["<Ctrl><Shift>c"]),
+    ("win.paste", &["<Ctrl><Shift>v"]),
+    ("win.find", &["<Ctrl><Shift>f"]),
+    ("win.clear-scrollback", &["<Ctrl><Shift>k"]),
+    ("win.toggle-broadcast", &["<Ctrl><Shift>b"]),
+    ("win.new-tab", &["<Ctrl><Shift>t"]),
+    ("win.close-tab", &["<Ctrl><Shift>w"]),
+    ("win.rename-tab", &["<Ctrl><Shift>r"]),
+    ("win.next-tab", &["<Ctrl>Page_Down"]),
+    ("win.previous-tab", &["<Ctrl>Page_Up"]),
+    ("win.move-tab-right", &["<Ctrl><Shift>Page_Down"]),
+    ("win.move-tab-left", &["<Ctrl><Shift>Page_Up"]),
+    ("win.split-horizontal", &["<Ctrl><Shift>e"]),
+    ("win.split-vertical", &["<Ctrl><Shift>o"]),
+    ("win.close-pane", &["<Ctrl><Shift>x"]),
+    ("win.focus-next", &["<Ctrl>Tab"]),
+    // Shift turns Tab into ISO_Left_Tab on most layouts
+    (
+        "win.focus-previous",
+        &["<Ctrl><Shift>Tab", "<Ctrl><Shift>ISO_Left_Tab"],
+    ),
+    ("win.focus-left", &["<Alt>Left"]),
+    ("win.focus-right", &["<Alt>Right"]),
+    ("win.focus-up", &["<Alt>Up"]),
+    ("win.focus-down", &["<Alt>Down"]),
+    ("win.swap-left", &["<Alt><Shift>Left"]),
+    ("win.swap-right", &["<Alt><Shift>Right"]),
+    ("win.swap-up", &["<Alt><Shift>Up"]),
+    ("win.swap-down", &["<Alt><Shift>Down"]),
+    ("win.resize-wider", &["<Ctrl><Shift>Right"]),
+    ("win.resize-narrower", &["<Ctrl><Shift>Left"]),
+    ("win.resize-taller", &["<Ctrl><Shift>Down"]),
+    ("win.resize-shorter", &["<Ctrl><Shift>Up"]),
+    // "plus" needs Shift on most layouts, so "equal" works too
+    (
+        "win.zoom-in",
+        &["<Ctrl>plus", "<Ctrl>equal", "<Ctrl>KP_Add"],
+    ),
+    ("win.zoom-out", &["<Ctrl>minus", "<Ctrl>KP_Subtract"]),
+    ("win.zoom-reset", &["<Ctrl>0", "<Ctrl>KP_0"]),
+];
+
+// A single accelerator or a list; "" and [] both unbind
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Accels {
+    One(String),
+    Many(Vec<String>),
+}
+
+pub type Bindings = Vec<(String, Vec<String>)>;
+
+pub fn keybindings_path() -> PathBuf {
+    config_dir().join("keybindings.toml")
+}
+
+/// The default bindings with the overrides in `text` applied.
+pub fn parse(text: &str) -> Result<Bindings, String> {
+    let overrides: BTreeMap<String, Accels> = toml::from_str(text).map_err(|e| e.to_string())?;
+
+    let mut bindings: Bindings = DEFAULT_BINDINGS
+        .iter()
+        .map(|(action, accels)| {
+            (
+                action.to_string(),
+                accels.iter().map(|a| a.to_string()).collect(),
+            )
+        })
+        .collect();
+
+    for (action, accels) in overrides {
+        // The "win."/"app." prefix may be left out
+        let (_, slot) = bindings
+            .iter_mut()
+            .find(|(name, _)| {
+                *name == action || name.split_once('.').map(|(_, n)| n) == Some(&action)
+            })
+            .ok_or_else(|| format!("Unknown action '{action}'"))?;
+        *slot = match accels {
+            Accels::One(accel) if accel.is_empty() => Vec::new(),
+            Accels::One(accel) => vec![accel],
+            Accels::Many(accels) => accels,
+        };
+    }
+    Ok(bindings)
+}
+
+/// Load `keybindings.toml`; a missing file means the defaults.
+pub fn load() -> Result<Bindings, String> {
+    let path = keybindings_path();
+    match std::fs::read_to_string(&path) {
+        Ok(text) => parse(&text).map_err(|e| format!("{}: {e}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => parse(""),
+        Err(e) => Err(format!("{}: {e}", path.display())),
+    }
+}
+
+/// Install the keybindings on the application, falling back to the defaults
+/// when the file can't be used.
+pub fn apply(app: &gtk::Application) {
+    let bindings = load().unwrap_or_else(|e| {
+        eprintln!("Ignoring keybindings: {e}");
+        // Shown once a window is up, like config.toml errors
+        glib::idle_add_local_once(move || show_error_dialog("Invalid keybindings", &e));
+        parse("").expect("Default keybindings are valid")
+    });
+
+    for (action, accels) in &bindings {
+        let valid: Vec<&str> = accels
+            .iter()
+            .map(String::as_str)
+            .filter(|accel| {
+                let ok = gtk::accelerator_parse(*accel).is_some();
+                if !ok {
+                    eprintln!("Ignoring invalid accelerator '{accel}' for {action}");
+                }
+                ok
+            })
+            .collect();
+        app.set_accels_for_action(action, &valid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accels<'a>(bindings: &'a Bindings, action: &str) -> &'a [String] {
+        &bindings.iter().find(|(a, _)| a == action).unwrap().1
+    }
+
+    #[test]
+    fn empty_file_uses_defaults() {
+        let bindings = parse("").unwrap();
+        assert_eq!(bindings.len(), DEFAULT_BINDINGS.len());
+        assert_eq!(accels(&bindings, "win.save-layout"), ["<Ctrl><Shift>s"]);
+    }
+
+    #[test]
+    fn overrides_and_unbinding() {
+        let bindings = parse(
+            r#"
+            "win.save-layout" = []
+            load-layout = ""
+            copy = "<Ctrl>Insert"
+            "win.new-tab" = ["<Ctrl>t", "<Ctrl><Shift>t"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(accels(&bindings, "win.save-layout").is_empty());
+        assert!(accels(&bindings, "win.load-layout").is_empty());
+        assert_eq!(accels(&bindings, "win.copy"), ["<Ctrl>Insert"]);
+        assert_eq!(accels(&bindings, "win.new-tab").len(), 2);
+        // Untouched actions keep their defaults
+        assert_eq!(accels(&bindings, "win.paste"), ["<Ctrl><Shift>v"]);
+    }
+
+    #[test]
+    fn defaults_leave_ctrl_letters_to_the_panes() {
+        // Ctrl+S, Ctrl+O and friends belong to vim, nano and readline
+        for (action, accels) in DEFAULT_BINDINGS {
+            for accel in *accels {
+                let key = accel
+                    .strip_prefix("<Ctrl>")
+                    .or_else(|| accel.strip_prefix("<Primary>"));
+                assert!(
+                    !key.is_some_and(|k| k.len() == 1 && k.chars().all(|c| c.is_ascii_alphabetic())),
+                    "{action} takes {accel}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_actions_are_rejected() {
+        assert!(parse(r#"frobnicate = "<Ctrl>f""#).is_err());
+        assert!(parse(r#"copy = 3"#).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use serde::Deserialize;
+
+use crate::constants::TERMINAL_COLORS;
+use crate::layout::types::{ExitPolicy, Scrollback};
+
+pub mod keybindings;
+pub mod schemes;
+pub mod watch;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub terminal: TerminalConfig,
+    pub session: SessionConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    // Size of new windows
+    pub width: i32,
+    pub height: i32,
+    pub dark_theme: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            width: 1000,
+            height: 700,
+            dark_theme: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TerminalConfig {
+    // Program started in new panes (default: $SHELL, then the login shell)
+    pub shell: Option<String>,
+    // Pango font description, e.g. "Monospace 11"
+    pub font: Option<String>,
+    // Lines kept in the scrollback, or "unlimited" (unset = VTE default)
+    pub scrollback_lines: Option<Scrollback>,
+    // Title bar accent colours offered in the colour picker
+    pub palette: Vec<String>,
+    // Colour scheme id for panes without their own (unset = VTE defaults)
+    pub color_scheme: Option<String>,
+    // Opens Ctrl+clicked file:line links, e.g. "code --goto {file}:{line}:{column}"
+    pub editor: Option<String>,
+    // When a pane's process exits: "close", "hold" or "restart" (panes can override it)
+    pub on_exit: ExitPolicy,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        TerminalConfig {
+            shell: None,
+            font: None,
+            scrollback_lines: None,
+            palette: TERMINAL_COLORS.iter().map(|c| c.to_string()).collect(),
+            color_scheme: None,
+            editor: None,
+            on_exit: ExitPolicy::Close,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    // Save the open layout on exit and restore it on the next start
+    pub enabled: bool,
+    // Seconds between background saves while running (0 = only on exit)
+    pub autosave_interval: u32,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig {
+            enabled: true,
+            autosave_interval: 60,
+        }
+    }
+}
+
+thread_local! {
+    static CONFIG: RefCell<Rc<Config>> = RefCell::new(Rc::new(Config::default()));
+}
+
+pub fn config_dir() -> PathBuf {
+    glib::user_config_dir().join("spliterma")
+}
+
+pub fn config_path() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+/// The configuration currently in effect.
+pub fn get() -> Rc<Config> {
+    CONFIG.with(|c| c.borrow().clone())
+}
+
+/// Load `config.toml`; a missing file means all defaults.
+pub fn load() -> Result<Config, String> {
+    let path = config_path();
+    let config = match std::fs::read_to_string(&path) {
+        Ok(text) => parse(&text).map_err(|e| format!("{}: {e}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+        Err(e) => return Err(format!("{}: {e}", path.display())),
+    };
+
+    // Scheme files live next to config.toml, so this can't be checked in parse()
+    if let Some(id) = &config.terminal.color_scheme {
+        if schemes::find(id).is_none() {
+            return Err(format!(
+                "{}: unknown terminal.color_scheme '{id}'",
+                path.display()
+            ));
+        }
+    }
+    Ok(config)
+}
+
+pub fn parse(text: &str) -> Result<Config, String> {
+    let config: Config = toml::from_str(text).map_err(|e| e.to_string())?;
+    config.validate()?;
+    Ok(config)
+}
+
+impl Config {
+    // Checks the types alone can't express
+    fn validate(&self) -> Result<(), String> {
+        if self.window.width < 200 || self.window.height < 100 {
+            return Err("window.width and window.height are too small".into());
+        }
+        if self
+            .terminal
+            .shell
+            .as_deref()
+            .is_some_and(|s| s.trim().is_empty())
+        {
+            return Err("terminal.shell is empty".into());
+        }
+        if self
+            .terminal
+            .font
+            .as_deref()
+            .is_some_and(|f| f.trim().is_empty())
+        {
+            return Err("terminal.font is empty".into());
+        }
+        if self
+            .terminal
+            .editor
+            .as_deref()
+            .is_some_and(|e| e.trim().is_empty())
+        {
+            return Err("terminal.editor is empty".into());
+        }
+        if self.terminal.palette.is_empty() {
+            return Err("terminal.palette needs at least one colour".into());
+        }
+        if let Some(bad) = self.terminal.palette.iter().find(|c| !is_hex_color(c)) {
+            return Err(format!(
+                "terminal.palette: '{bad}' is not a #RGB or #RRGGBB colour"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Accent colour for a pane's colour index.
+    pub fn accent_color(&self, index: usize) -> &str {
+        let palette = &self.terminal.palette;
+        &palette[index % palette.len()]
+    }
+
+    /// A pane's own colour if it's valid, else its palette entry (layouts before 2.2).
+    pub fn pane_accent(&self, color: Option<&str>, index: usize) -> String {
+        color
+            .filter(|c| is_hex_color(c))
+            .unwrap_or_else(|| self.accent_color(index))
+            .to_string()
+    }
+}
+
+// #RGB or #RRGGBB
+pub(crate) fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Load the config at startup. A broken file is reported and the defaults are used.
+pub fn init() -> Result<(), String> {
+    schemes::reload();
+    let result = load();
+    let config = result.as_ref().cloned().unwrap_or_default();
+    CONFIG.with(|c| *c.borrow_mut() = Rc::new(config));
+    result.map(|_| ())
+}
+
+/// Re-read the config. On error the current config stays in effect.
+pub fn reload() -> Result<Rc<Config>, String> {
+    schemes::reload();
+    let config = Rc::new(load()?);
+    CONFIG.with(|c| *c.borrow_mut() = config.clone());
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_uses_defaults() {
+        let config = parse("").unwrap();
+        assert!(config.session.enabled);
+        assert_eq!(config.session.autosave_interval, 60);
+    }
+
+    #[test]
+    fn session_can_be_disabled() {
+        let config = parse("[session]\nenabled = false\n").unwrap();
+        assert!(!config.session.enabled);
+        assert_eq!(config.session.autosave_interval, 60);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(parse("[session]\nenable = false\n").is_err());
+    }
+
+    #[test]
+    fn terminal_and_window_settings() {
+        let config = parse(
+            r##"
+            [window]
+            width = 1400
+            dark_theme = false
+
+            [terminal]
+            shell = "/usr/bin/fish"
+            font = "JetBrains Mono 12"
+            scrollback_lines = 20000
+            palette = ["#ff0000", "#0f0"]
+            on_exit = "hold"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(config.window.width, 1400);
+        assert_eq!(config.window.height, 700);
+        assert_eq!(config.terminal.on_exit, ExitPolicy::Hold);
+        assert_eq!(Config::default().terminal.on_exit, ExitPolicy::Close);
+        assert!(!config.window.dark_theme);
+        assert_eq!(config.terminal.shell.as_deref(), Some("/usr/bin/fish"));
+        assert_eq!(
+            config.terminal.scrollback_lines,
+            Some(Scrollback::Lines(20000))
+        );
+        let unlimited = parse("[terminal]\nscrollback_lines = \"unlimited\"\n").unwrap();
+        assert_eq!(
+            unlimited.terminal.scrollback_lines,
+            Some(Scrollback::Unlimited)
+        );
+        assert_eq!(config.accent_color(3), "#0f0");
+        // Custom palettes longer than the built-in one are used in full
+        let mut long = Config::default();
+        long.terminal.palette = (0..10).map(|i| format!("#00000{i}")).collect();
+        assert_eq!(long.accent_color(9), "#000009");
+        assert_eq!(long.accent_color(10), "#000000");
+        assert_eq!(config.pane_accent(Some("#123456"), 0), "#123456");
+        assert_eq!(config.pane_accent(Some("red; }"), 1), "#0f0");
+        assert_eq!(config.pane_accent(None, 2), "#ff0000");
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(parse("[window]\nwidth = 10\n").is_err());
+        assert!(parse("[terminal]\npalette = []\n").is_err());
+        assert!(parse("[terminal]\npalette = [\"blue\"]\n").is_err());
+        assert!(parse("[terminal]\nshell = \" \"\n").is_err());
+        assert!(parse("[terminal]\nscrollback_lines = -5\n").is_err());
+        assert!(parse("[terminal]\non_exit = \"linger\"\n").is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde::Deserialize;
+
+use crate::config::{config_dir, is_hex_color};
+
+/// Terminal colours: foreground, background and the 16-colour ANSI palette.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorScheme {
+    // File stem for user schemes, e.g. "dracula"
+    pub id: String,
+    pub name: String,
+    pub foreground: String,
+    pub background: String,
+    pub cursor: Option<String>,
+    pub palette: Vec<String>,
+}
+
+// Format of ~/.config/spliterma/schemes/<id>.toml
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemeFile {
+    name: Option<String>,
+    foreground: String,
+    background: String,
+    cursor: Option<String>,
+    palette: Vec<String>,
+}
+
+struct Bundled {
+    id: &'static str,
+    name: &'static str,
+    foreground: &'static str,
+    background: &'static str,
+    cursor: Option<&'static str>,
+    palette: [&'static str; 16],
+}
+
+const SOLARIZED_PALETTE: [&str; 16] = [
+    "#073642", "#dc322f", "#859900", "#b58900", "#268bd2", "#d33682", "#2aa198", "#eee8d5",
+    "#002b36", "#cb4b16", "#586e75", "#657b83", "#839496", "#6c71c4", "#93a1a1", "#fdf6e3",
+];
+
+const BUNDLED: &[Bundled] = &[
+    Bundled {
+        id: "solarized-dark",
+        name: "Solarized Dark",
+        foreground: "#839496",
+        background: "#002b36",
+        cursor: None,
+        palette: SOLARIZED_PALETTE,
+    },
+    Bundled {
+        id: "solarized-light",
+        name: "Solarized Light",
+        foreground: "#657b83",
+        background: "#fdf6e3",
+        cursor: None,
+        palette: SOLARIZED_PALETTE,
+    },
+    Bundled {
+        id: "dracula",
+        name: "Dracula",
+        foreground: "#f8f8f2",
+        background: "#282a36",
+        cursor: Some("#f8f8f2"),
+        palette: [
+            "#21222c", "#ff5555", "#50fa7b", "#f1fa8c", "#bd93f9", "#ff79c6", "#8be9fd", "#f8f8f2",
+            "#6272a4", "#ff6e6e", "#69ff94", "#ffffa5", "#d6acff", "#ff92df", "#a4ffff", "#ffffff",
+        ],
+    },
+    Bundled {
+        id: "gruvbox-dark",
+        name: "Gruvbox Dark",
+        foreground: "#ebdbb2",
+        background: "#282828",
+        cursor: None,
+        palette: [
+            "#282828", "#cc241d", "#98971a", "#d79921", "#458588", "#b16286", "#689d6a", "#a89984",
+            "#928374", "#fb4934", "#b8bb26", "#fabd2f", "#83a598", "#d3869b", "#8ec07c", "#ebdbb2",
+        ],
+    },
+    Bundled {
+        id: "gruvbox-light",
+        name: "Gruvbox Light",
+        foreground: "#3c3836",
+        background: "#fbf1c7",
+        cursor: None,
+        palette: [
+            "#fbf1c7", "#cc241d", "#98971a", "#d79921", "#458588", "#b16286", "#689d6a", "#7c6f64",
+            "#928374", "#9d0006", "#79740e", "#b57614", "#076678", "#8f3f71", "#427b58", "#3c3836",
+        ],
+    },
+];
+
+pub fn bundled() -> Vec<ColorScheme> {
+    BUNDLED
+        .iter()
+        .map(|b| ColorScheme {
+            id: b.id.into(),
+            name: b.name.into(),
+            foreground: b.foreground.into(),
+            background: b.background.into(),
+            cursor: b.cursor.map(str::to_string),
+            palette: b.palette.iter().map(|c| c.to_string()).collect(),
+        })
+        .collect()
+}
+
+pub fn schemes_dir() -> PathBuf {
+    config_dir().join("schemes")
+}
+
+/// Parse a user scheme file. `id` is the file name without `.toml`.
+pub fn parse(id: &str, text: &str) -> Result<ColorScheme, String> {
+    let file: SchemeFile = toml::from_str(text).map_err(|e| e.to_string())?;
+
+    // VTE takes 8 or 16 colours and derives the rest
+    if !matches!(file.palette.len(), 8 | 16) {
+        return Err(format!(
+            "palette needs 8 or 16 colours, got {}",
+            file.palette.len()
+        ));
+    }
+    let colors = [&file.foreground, &file.background]
+        .into_iter()
+        .chain(file.cursor.as_ref())
+        .chain(&file.palette);
+    for color in colors {
+        if !is_hex_color(color) {
+            return Err(format!("'{color}' is not a #RGB or #RRGGBB colour"));
+        }
+    }
+
+    Ok(ColorScheme {
+        id: id.to_string(),
+        name: file.name.unwrap_or_else(|| id.to_string()),
+        foreground: file.foreground,
+        background: file.background,
+        cursor: file.cursor,
+        palette: file.palette,
+    })
+}
+
+// Broken files are reported and skipped so one typo doesn't hide the rest
+fn load_dir(dir: &Path) -> Vec<ColorScheme> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_string_lossy();
+            let result = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| parse(&id, &text));
+            result
+                .inspect_err(|e| eprintln!("Ignoring colour scheme {}: {e}", path.display()))
+                .ok()
+        })
+        .collect()
+}
+
+/// Bundled schemes followed by the user's; a user scheme replaces a bundled one with the same id.
+pub fn merge(bundled: Vec<ColorScheme>, user: Vec<ColorScheme>) -> Vec<ColorScheme> {
+    let mut schemes: Vec<ColorScheme> = bundled
+        .into_iter()
+        .filter(|b| !user.iter().any(|u| u.id == b.id))
+        .collect();
+    schemes.extend(user);
+    schemes
+}
+
+thread_local! {
+    static SCHEMES: RefCell<Rc<Vec<ColorScheme>>> = RefCell::new(Rc::new(bundled()));
+}
+
+/// Re-read the user schemes from disk.
+pub fn reload() {
+    let schemes = merge(bundled(), load_dir(&schemes_dir()));
+    SCHEMES.with(|s| *s.borrow_mut() = Rc::new(schemes));
+}
+
+pub fn all() -> Rc<Vec<ColorScheme>> {
+    SCHEMES.with(|s| s.borrow().clone())
+}
+
+pub fn find(id: &str) -> Option<ColorScheme> {
+    all().iter().find(|s| s.id == id).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_schemes_are_valid() {
+        for scheme in bundled() {
+            let colors = [&scheme.foreground, &scheme.background]
+                .into_iter()
+                .chain(scheme.cursor.as_ref())
+                .chain(&scheme.palette);
+            assert!(colors.into_iter().all(|c| is_hex_color(c)), "{}", scheme.id);
+            assert_eq!(scheme.palette.len(), 16);
+        }
+    }
+
+    #[test]
+    fn user_scheme_file() {
+        let scheme = parse(
+            "mine",
+            r##"
+            foreground = "#ffffff"
+            background = "#000"
+            palette = ["#000000", "#ff0000", "#00ff00", "#ffff00",
+                       "#0000ff", "#ff00ff", "#00ffff", "#ffffff"]
+            "##,
+        )
+        .unwrap();
+        assert_eq!(scheme.name, "mine");
+        assert_eq!(scheme.cursor, None);
+
+        let short = r##"foreground = "#fff"
+            background = "#000"
+            palette = ["#000000", "#ff0000"]"##;
+        assert!(parse("short", short).is_err());
+        let bad = r##"foreground = "white"
+            background = "#000"
+            palette = ["#000", "#000", "#000", "#000", "#000", "#000", "#000", "#000"]"##;
+        assert!(parse("bad", bad).is_err());
+    }
+
+    #[test]
+    fn user_schemes_replace_bundled_ones() {
+        let mut dracula = bundled().remove(2);
+        dracula.background = "#000000".into();
+        let schemes = merge(bundled(), vec![dracula.clone()]);
+        assert_eq!(schemes.len(), bundled().len());
+        assert_eq!(schemes.iter().filter(|s| s.id == "dracula").count(), 1);
+        assert_eq!(schemes.last(), Some(&dracula));
+    }
+}
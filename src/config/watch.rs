@@ -0,0 +1,79 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk::gio;
+use gtk::prelude::*;
+use gtk4 as gtk;
+
+use crate::config::config_dir;
+use crate::config::schemes::schemes_dir;
+
+/// Call `on_change` whenever one of the `.toml`/`.css` files in the config directory
+/// (or its colour schemes) changes.
+/// Editors tend to save in several steps, so bursts of events are coalesced.
+pub fn watch(app: &gtk::Application, on_change: impl Fn(&gtk::Application) + 'static) {
+    let pending = Rc::new(Cell::new(false));
+    let on_change: Rc<dyn Fn(&gtk::Application)> = Rc::new(on_change);
+    let mut monitors = Vec::new();
+
+    for path in [config_dir(), schemes_dir()] {
+        // inotify can only watch a directory that exists
+        if let Err(e) = std::fs::create_dir_all(&path) {
+            eprintln!("Not watching {}: {e}", path.display());
+            continue;
+        }
+        let dir = gio::File::for_path(&path);
+        match dir.monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE) {
+            Ok(monitor) => {
+                connect_monitor(&monitor, app, pending.clone(), on_change.clone());
+                monitors.push(monitor);
+            }
+            Err(e) => eprintln!("Not watching {}: {e}", path.display()),
+        }
+    }
+
+    // The monitors stop when dropped, so keep them alive with the app
+    unsafe {
+        app.set_data("config-monitors", monitors);
+    }
+}
+
+fn connect_monitor(
+    monitor: &gio::FileMonitor,
+    app: &gtk::Application,
+    pending: Rc<Cell<bool>>,
+    on_change: Rc<dyn Fn(&gtk::Application)>,
+) {
+    let app_weak = app.downgrade();
+    monitor.connect_changed(move |_, file, other, event| {
+        if matches!(
+            event,
+            gio::FileMonitorEvent::AttributeChanged
+                | gio::FileMonitorEvent::PreUnmount
+                | gio::FileMonitorEvent::Unmounted
+        ) {
+            return;
+        }
+        let is_config = |f: &gio::File| {
+            f.path()
+                .is_some_and(|p| p.extension().is_some_and(|e| e == "toml" || e == "css"))
+        };
+        if !is_config(file) && !other.is_some_and(is_config) {
+            return;
+        }
+        if pending.replace(true) {
+            return;
+        }
+
+        let pending = pending.clone();
+        let on_change = on_change.clone();
+        let app_weak = app_weak.clone();
+        glib::timeout_add_local_once(Duration::from_millis(300), move || {
+            pending.set(false);
+            if let Some(app) = app_weak.upgrade() {
+                on_change(&app);
+            }
+        });
+    });
+}
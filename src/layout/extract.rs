@@ -1,102 +1,111 @@
-use crate::layout::types::{SplitType, TerminalLayout};
+use crate::layout::types::{TabLayout, TerminalLayout, WindowLayout};
+use crate::ui::broadcast::broadcast_group;
+use crate::ui::scrollback::log_path;
+use crate::ui::tabs::{tab_title, window_notebook};
+use crate::ui::terminal::{
+    find_terminal_in_container, pane_color_scheme, pane_exit_policy, pane_scrollback,
+};
+use crate::ui::workspace::{Workspace, pane_uuid};
 use gtk4 as gtk;
-use vte4::Terminal;
 use vte4::prelude::*; // for TerminalExt::current_directory_uri()
 
-// Make Paned first, then Box; Box falls through to child if it's just a wrapper.
+// The tree shape comes from the workspace model; only live pane state is read from widgets.
 pub fn extract_layout(widget: &gtk::Widget) -> Result<TerminalLayout, String> {
-    // 1) Split containers
-    if let Ok(paned) = widget.clone().downcast::<gtk::Paned>() {
-        let split_type = match paned.orientation() {
-            gtk::Orientation::Horizontal => SplitType::Horizontal,
-            gtk::Orientation::Vertical => SplitType::Vertical,
-            _ => return Err("Unknown orientation".to_string()),
-        };
+    let workspace = Workspace::for_widget(widget)
+        .ok_or_else(|| "Widget is not part of a workspace".to_string())?;
 
-        let mut children = Vec::new();
-        if let Some(start_child) = paned.start_child() {
-            children.push(extract_layout(&start_child)?);
-        }
-        if let Some(end_child) = paned.end_child() {
-            children.push(extract_layout(&end_child)?);
+    let mut tree = workspace.snapshot();
+    tree.for_each_pane_mut(|id, pane| {
+        if let Some(container) = workspace.container(id) {
+            extract_pane(&container, pane);
         }
+    });
 
-        return Ok(TerminalLayout {
-            name: "Split".to_string(),
-            color_index: 0,
-            working_dir: String::new(),
-            split_type: Some(split_type),
-            children,
-        });
-    }
+    tree.to_layout()
+        .ok_or_else(|| "Workspace has no panes".to_string())
+}
 
-    // 2) Terminal container OR transparent wrapper
-    if let Ok(container) = widget.clone().downcast::<gtk::Box>() {
-        if let Some(terminal) = find_terminal_in_container(&container) {
-            // Prefer live cwd from VTE (updates after `cd`), fall back to stored data
-            let live_cwd = terminal
-                .current_directory_uri()
-                .and_then(|u| file_uri_to_path(&u));
+/// Every tab of a window, in tab order, plus the window's size and state.
+pub fn extract_window(window: &gtk::ApplicationWindow) -> Result<WindowLayout, String> {
+    let notebook = window_notebook(window).ok_or_else(|| "Window has no tabs".to_string())?;
+    let mut tabs = Vec::new();
+    for i in 0..notebook.n_pages() {
+        if let Some(page) = notebook.nth_page(Some(i)) {
+            tabs.push(TabLayout {
+                title: tab_title(&notebook, &page),
+                root: extract_layout(&page)?,
+                focused: Workspace::for_widget(&page).and_then(|w| w.focused_pane()),
+            });
+        }
+    }
+    if tabs.is_empty() {
+        return Err("Window has no tabs".into());
+    }
 
-            let working_dir = if let Some(path) = live_cwd {
-                path
-            } else {
-                unsafe {
-                    terminal
-                        .data::<String>("working_dir")
-                        .map(|d| d.as_ref().clone())
-                        .unwrap_or_else(|| {
-                            std::env::current_dir()
-                                .unwrap_or_default()
-                                .to_string_lossy()
-                                .to_string()
-                        })
-                }
-            };
+    // GTK keeps the default size in sync with the unmaximized size
+    let (width, height) = window.default_size();
+    Ok(WindowLayout {
+        size: (width > 0 && height > 0).then_some([width, height]),
+        maximized: window.is_maximized(),
+        fullscreen: window.is_fullscreen(),
+        tabs,
+        active_tab: notebook.current_page().unwrap_or(0) as usize,
+    })
+}
 
-            let name = extract_terminal_name(&container).unwrap_or_else(|| "Terminal".to_string());
-            let color_index = extract_color_index(&container);
+/// Current settings of a single pane, as they would be saved.
+pub(crate) fn pane_layout(container: &gtk::Box) -> TerminalLayout {
+    let mut pane = TerminalLayout::default();
+    extract_pane(container, &mut pane);
+    pane
+}
 
-            return Ok(TerminalLayout {
-                name,
-                color_index,
-                working_dir,
-                split_type: None,
-                children: vec![],
-            });
-        }
+fn extract_pane(container: &gtk::Box, pane: &mut TerminalLayout) {
+    pane.id = pane_uuid(container);
+    pane.broadcast_group = broadcast_group(container);
+    if let Some(terminal) = find_terminal_in_container(container) {
+        pane.color_scheme = pane_color_scheme(&terminal);
+        pane.scrollback_lines = pane_scrollback(&terminal);
+        pane.on_exit = pane_exit_policy(&terminal);
+        pane.font_scale = Some(terminal.font_scale()).filter(|s| *s != 1.0);
+        pane.log_file = log_path(&terminal).map(|p| p.to_string_lossy().to_string());
 
-        // Transparent wrapper: recurse into first child if any
-        if let Some(child) = container.first_child() {
-            return extract_layout(&child);
-        }
+        // Prefer live cwd from VTE (updates after `cd`), fall back to stored data
+        let live_cwd = terminal
+            .current_directory_uri()
+            .and_then(|u| file_uri_to_path(&u));
 
-        return Err("Empty gtk::Box with no children".to_string());
+        pane.working_dir = if let Some(path) = live_cwd {
+            path
+        } else {
+            unsafe {
+                terminal
+                    .data::<String>("working_dir")
+                    .map(|d| d.as_ref().clone())
+                    .unwrap_or_else(|| {
+                        std::env::current_dir()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                            .to_string()
+                    })
+            }
+        };
     }
 
-    Err(format!("Unsupported widget type: {:?}", widget.type_()))
-}
-
-fn find_terminal_in_container(container: &gtk::Box) -> Option<Terminal> {
-    // Look through container children to find the terminal
-    let mut child = container.first_child();
-    while let Some(widget) = child {
-        if let Some(terminal) = widget.clone().downcast::<Terminal>().ok() {
-            return Some(terminal);
-        }
-        child = widget.next_sibling();
+    if let Some(name) = extract_terminal_name(container) {
+        pane.name = name;
     }
-    None
+    pane.color = extract_color(container);
 }
 
-fn extract_terminal_name(container: &gtk::Box) -> Option<String> {
+pub(crate) fn extract_terminal_name(container: &gtk::Box) -> Option<String> {
     // Find the title label in the container
     let mut child = container.first_child();
     while let Some(widget) = child {
-        if let Some(title_bar) = widget.clone().downcast::<gtk::Box>().ok() {
+        if let Ok(title_bar) = widget.clone().downcast::<gtk::Box>() {
             let mut title_child = title_bar.first_child();
             while let Some(title_widget) = title_child {
-                if let Some(label) = title_widget.clone().downcast::<gtk::Label>().ok() {
+                if let Ok(label) = title_widget.clone().downcast::<gtk::Label>() {
                     return Some(label.text().to_string());
                 }
                 title_child = title_widget.next_sibling();
@@ -107,18 +116,15 @@ fn extract_terminal_name(container: &gtk::Box) -> Option<String> {
     None
 }
 
-pub fn extract_color_index(container: &gtk::Box) -> usize {
-    // `data::<usize>` returns Option<NonNull<usize>> in your setup,
-    // so we must dereference it explicitly.
+pub fn extract_color(container: &gtk::Box) -> Option<String> {
     unsafe {
-        match container.data::<usize>("color_index") {
-            Some(ptr) => *ptr.as_ref(),
-            None => 0,
-        }
+        container
+            .data::<String>("color")
+            .map(|color| color.as_ref().clone())
     }
 }
 
-fn file_uri_to_path(uri: &str) -> Option<String> {
+pub(crate) fn file_uri_to_path(uri: &str) -> Option<String> {
     if !uri.starts_with("file://") {
         return None;
     }
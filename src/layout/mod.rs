@@ -1,3 +1,5 @@
 pub mod extract;
 pub mod persist;
+pub mod session;
+pub mod tree;
 pub mod types;
@@ -1,12 +1,18 @@
+use std::path::Path;
+
 use gtk::prelude::*;
 use gtk4 as gtk;
 use serde_json;
 
-use crate::layout::extract::extract_layout;
-use crate::layout::types::{SavedLayout, SplitType};
+use crate::app::{apply_geometry, open_saved_window};
+use crate::layout::extract::extract_window;
+use crate::layout::types::SavedLayout;
+use crate::ui::tabs::{set_tabs, window_notebook};
 use crate::util::errors::show_error_dialog;
 
-pub fn save_layout(root_widget: &gtk::Widget) {
+// FileChooserDialog is deprecated since GTK 4.10 but still works fine
+#[allow(deprecated)]
+pub fn save_layout(window: &gtk::ApplicationWindow) {
     let dialog = gtk::FileChooserDialog::builder()
         .title("Save Layout")
         .action(gtk::FileChooserAction::Save)
@@ -17,36 +23,16 @@ pub fn save_layout(root_widget: &gtk::Widget) {
     dialog.add_button("Save", gtk::ResponseType::Accept);
     dialog.set_current_name("terminal_layout.json");
 
-    if let Some(window) = root_widget
-        .root()
-        .and_then(|r| r.downcast::<gtk::Window>().ok())
-    {
-        dialog.set_transient_for(Some(&window));
-    }
+    dialog.set_transient_for(Some(window));
 
-    let root_clone = root_widget.clone();
+    let win = window.clone();
     dialog.connect_response(move |d, resp| {
         if resp == gtk::ResponseType::Accept {
             if let Some(file) = d.file() {
                 if let Some(path) = file.path() {
-                    match extract_layout(&root_clone) {
-                        Ok(layout) => {
-                            let saved = SavedLayout {
-                                version: "1.0".into(),
-                                root: layout,
-                            };
-                            match serde_json::to_string_pretty(&saved) {
-                                Ok(json) => {
-                                    if let Err(e) = std::fs::write(&path, json) {
-                                        show_error_dialog("Failed to save layout", &e.to_string());
-                                    } else {
-                                        println!("Layout saved to: {}", path.display());
-                                    }
-                                }
-                                Err(e) => show_error_dialog("Serialize error", &e.to_string()),
-                            }
-                        }
-                        Err(e) => show_error_dialog("Extract layout failed", &e),
+                    match write_layout_file(&win, &path) {
+                        Ok(()) => println!("Layout saved to: {}", path.display()),
+                        Err(e) => show_error_dialog("Failed to save layout", &e),
                     }
                 }
             }
@@ -57,6 +43,14 @@ pub fn save_layout(root_widget: &gtk::Widget) {
     dialog.present();
 }
 
+/// Save one window's tabs; the session file is the one that covers all of them.
+pub fn write_layout_file(window: &gtk::ApplicationWindow, path: &Path) -> Result<(), String> {
+    let saved = extract_window(window).map(|layout| SavedLayout::new(vec![layout]))?;
+    let json = serde_json::to_string_pretty(&saved).map_err(|e| format!("Serialize error: {e}"))?;
+    std::fs::write(path, json).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[allow(deprecated)]
 pub fn load_layout(window: &gtk::ApplicationWindow) {
     let dialog = gtk::FileChooserDialog::builder()
         .title("Load Layout")
@@ -78,18 +72,11 @@ pub fn load_layout(window: &gtk::ApplicationWindow) {
         if resp == gtk::ResponseType::Accept {
             if let Some(file) = d.file() {
                 if let Some(path) = file.path() {
-                    match std::fs::read_to_string(&path) {
-                        Ok(json) => match serde_json::from_str::<SavedLayout>(&json) {
-                            Ok(saved) => match build_layout_from_data(&saved.root) {
-                                Ok(container) => {
-                                    win.set_child(Some(&container));
-                                    println!("Layout loaded from: {}", path.display());
-                                }
-                                Err(e) => show_error_dialog("Build layout failed", &e),
-                            },
-                            Err(e) => show_error_dialog("Parse error", &e.to_string()),
-                        },
-                        Err(e) => show_error_dialog("Read file failed", &e.to_string()),
+                    match read_layout_file(&path).and_then(|saved| load_into(&win, &saved)) {
+                        Ok(()) => {
+                            println!("Layout loaded from: {}", path.display());
+                        }
+                        Err(e) => show_error_dialog("Load layout failed", &e),
                     }
                 }
             }
@@ -100,43 +87,27 @@ pub fn load_layout(window: &gtk::ApplicationWindow) {
     dialog.present();
 }
 
-pub fn build_layout_from_data(
-    layout: &crate::layout::types::TerminalLayout,
-) -> Result<gtk::Box, String> {
-    use crate::ui::terminal::create_terminal_with_title;
-
-    if layout.split_type.is_none() {
-        let container =
-            create_terminal_with_title(&layout.name, layout.color_index, Some(&layout.working_dir));
-        Ok(container)
-    } else {
-        let children = &layout.children;
-        if children.len() != 2 {
-            return Err("Split must have exactly 2 children".into());
+// The first saved window replaces this one's tabs, any others open as new windows
+pub(crate) fn load_into(
+    window: &gtk::ApplicationWindow,
+    saved: &SavedLayout,
+) -> Result<(), String> {
+    let windows = saved.windows();
+    let (first, rest) = windows.split_first().ok_or("Layout has no windows")?;
+    let notebook = window_notebook(window).ok_or("Window has no tabs")?;
+
+    set_tabs(&notebook, first)?;
+    apply_geometry(window, first);
+
+    if let Some(app) = window.application() {
+        for layout in rest {
+            open_saved_window(&app, layout)?;
         }
-
-        let orientation = match layout.split_type {
-            Some(SplitType::Horizontal) => gtk::Orientation::Horizontal,
-            Some(SplitType::Vertical) => gtk::Orientation::Vertical,
-            None => unreachable!(),
-        };
-
-        let paned = gtk::Paned::new(orientation);
-        paned.set_hexpand(true);
-        paned.set_vexpand(true);
-        paned.set_wide_handle(true);
-
-        let start_child = build_layout_from_data(&children[0])?;
-        let end_child = build_layout_from_data(&children[1])?;
-
-        paned.set_start_child(Some(start_child.upcast_ref::<gtk::Widget>()));
-        paned.set_end_child(Some(end_child.upcast_ref::<gtk::Widget>()));
-
-        let wrapper = gtk::Box::new(gtk::Orientation::Vertical, 0);
-        wrapper.set_hexpand(true);
-        wrapper.set_vexpand(true);
-        wrapper.append(&paned);
-
-        Ok(wrapper)
     }
+    Ok(())
+}
+
+pub fn read_layout_file(path: &Path) -> Result<SavedLayout, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Read file failed: {e}"))?;
+    serde_json::from_str(&json).map_err(|e| format!("Parse error: {e}"))
 }
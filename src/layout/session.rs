@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use gtk::prelude::*;
+use gtk4 as gtk;
+
+use crate::layout::extract::extract_window;
+use crate::layout::persist::read_layout_file;
+use crate::layout::types::SavedLayout;
+
+pub fn session_path() -> PathBuf {
+    glib::user_state_dir()
+        .join("spliterma")
+        .join("session.json")
+}
+
+/// Write every open window to the session file. `closing` is left out unless it is
+/// the last window, so the session reflects what was open when the app went away.
+pub fn save_session(
+    app: &gtk::Application,
+    closing: Option<&gtk::ApplicationWindow>,
+) -> Result<(), String> {
+    let path = session_path();
+    let windows: Vec<gtk::ApplicationWindow> = app
+        .windows()
+        .into_iter()
+        .filter_map(|w| w.downcast::<gtk::ApplicationWindow>().ok())
+        .collect();
+
+    // Most recently focused window comes first; restore it last so it ends up on top
+    let layouts: Vec<_> = windows
+        .iter()
+        .rev()
+        .filter(|w| windows.len() == 1 || Some(*w) != closing)
+        // Windows whose last tab was just closed have nothing to restore
+        .filter_map(|w| extract_window(w).ok())
+        .collect();
+    if layouts.is_empty() {
+        return clear_session();
+    }
+
+    let saved = SavedLayout::new(layouts);
+    let json = serde_json::to_string_pretty(&saved).map_err(|e| e.to_string())?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    // Write next to the target and rename, so a crash never leaves half a file behind
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+/// The saved session, if there is one.
+pub fn load_session() -> Result<Option<SavedLayout>, String> {
+    let path = session_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    read_layout_file(&path).map(Some)
+}
+
+fn clear_session() -> Result<(), String> {
+    match std::fs::remove_file(session_path()) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
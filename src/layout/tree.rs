@@ -0,0 +1,818 @@
+use crate::layout::types::{SplitType, TerminalLayout};
+use crate::util::ids::next_pane_id;
+
+// Smallest share a pane can be resized down to
+const MIN_RATIO: f64 = 0.05;
+
+/// Runtime handle of a pane inside a `LayoutTree`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PaneId(usize);
+
+impl PaneId {
+    fn next() -> Self {
+        PaneId(next_pane_id())
+    }
+}
+
+// Panes are the common case, boxing them would only add indirection
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum LayoutNode {
+    /// A terminal; `pane` holds its leaf settings (split_type is always None)
+    Pane { id: PaneId, pane: TerminalLayout },
+    /// `ratios[i]` is the share of the split given to `children[i]`
+    Split {
+        split_type: SplitType,
+        children: Vec<LayoutNode>,
+        ratios: Vec<f64>,
+    },
+}
+
+/// Position of a pane inside the unit square covered by its tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// GTK-independent model of a pane tree. The widgets in `ui::workspace` are rendered from it.
+#[derive(Debug, Clone, Default)]
+pub struct LayoutTree {
+    root: Option<LayoutNode>,
+}
+
+impl LayoutTree {
+    pub fn with_pane(pane: TerminalLayout) -> (Self, PaneId) {
+        let id = PaneId::next();
+        let tree = LayoutTree {
+            root: Some(LayoutNode::Pane { id, pane }),
+        };
+        (tree, id)
+    }
+
+    pub fn from_layout(layout: &TerminalLayout) -> Result<Self, String> {
+        Ok(LayoutTree {
+            root: Some(node_from_layout(layout)?),
+        })
+    }
+
+    pub fn to_layout(&self) -> Option<TerminalLayout> {
+        self.root.as_ref().map(node_to_layout)
+    }
+
+    pub fn root(&self) -> Option<&LayoutNode> {
+        self.root.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Pane ids in reading order (start child before end child)
+    pub fn pane_ids(&self) -> Vec<PaneId> {
+        let mut ids = Vec::new();
+        if let Some(root) = &self.root {
+            collect_ids(root, &mut ids);
+        }
+        ids
+    }
+
+    pub fn pane(&self, id: PaneId) -> Option<&TerminalLayout> {
+        let path = self.path_of(id)?;
+        match self.node(&path)? {
+            LayoutNode::Pane { pane, .. } => Some(pane),
+            LayoutNode::Split { .. } => None,
+        }
+    }
+
+    pub fn for_each_pane_mut(&mut self, mut f: impl FnMut(PaneId, &mut TerminalLayout)) {
+        fn walk(node: &mut LayoutNode, f: &mut impl FnMut(PaneId, &mut TerminalLayout)) {
+            match node {
+                LayoutNode::Pane { id, pane } => f(*id, pane),
+                LayoutNode::Split { children, .. } => {
+                    for child in children {
+                        walk(child, f);
+                    }
+                }
+            }
+        }
+        if let Some(root) = &mut self.root {
+            walk(root, &mut f);
+        }
+    }
+
+    /// Add a new pane after `target`. If `target` already sits in a split of the
+    /// requested orientation the pane joins it as a sibling, otherwise `target` is
+    /// replaced by a new split holding both.
+    pub fn split(
+        &mut self,
+        target: PaneId,
+        split_type: SplitType,
+        pane: TerminalLayout,
+    ) -> Option<PaneId> {
+        let path = self.path_of(target)?;
+        let id = PaneId::next();
+
+        if let Some((&index, parent_path)) = path.split_last() {
+            if let Some(LayoutNode::Split {
+                split_type: parent_type,
+                children,
+                ratios,
+            }) = self.node_mut(parent_path)
+            {
+                if *parent_type == split_type {
+                    // The new sibling takes half of the target's share
+                    let half = ratios[index] / 2.0;
+                    ratios[index] = half;
+                    ratios.insert(index + 1, half);
+                    children.insert(index + 1, LayoutNode::Pane { id, pane });
+                    return Some(id);
+                }
+            }
+        }
+
+        let node = self.node_mut(&path)?;
+        let existing = std::mem::replace(
+            node,
+            LayoutNode::Split {
+                split_type,
+                children: Vec::new(),
+                ratios: Vec::new(),
+            },
+        );
+        if let LayoutNode::Split {
+            children, ratios, ..
+        } = node
+        {
+            *children = vec![existing, LayoutNode::Pane { id, pane }];
+            *ratios = vec![0.5, 0.5];
+        }
+        Some(id)
+    }
+
+    /// Remove `target`; a split left with a single child collapses into that child.
+    pub fn close(&mut self, target: PaneId) -> bool {
+        let Some(path) = self.path_of(target) else {
+            return false;
+        };
+        let Some((&index, parent_path)) = path.split_last() else {
+            self.root = None;
+            return true;
+        };
+
+        let Some(parent) = self.node_mut(parent_path) else {
+            return false;
+        };
+        if let LayoutNode::Split {
+            children, ratios, ..
+        } = parent
+        {
+            children.remove(index);
+            let share = ratios.remove(index);
+            // The freed space goes to the sibling that was next to the closed pane
+            let heir = index.saturating_sub(1).min(ratios.len().saturating_sub(1));
+            if let Some(r) = ratios.get_mut(heir) {
+                *r += share;
+            }
+
+            if children.len() == 1 {
+                let remaining = children.remove(0);
+                *parent = remaining;
+                self.merge_into_parent(parent_path);
+            }
+        }
+        true
+    }
+
+    // A split directly inside a split of the same orientation is spliced into it,
+    // so closing panes never leaves e.g. [a | [b | c]] where [a | b | c] would do.
+    fn merge_into_parent(&mut self, path: &[usize]) {
+        let Some((&index, parent_path)) = path.split_last() else {
+            return;
+        };
+        let Some(LayoutNode::Split {
+            split_type: inner_type,
+            ..
+        }) = self.node(path)
+        else {
+            return;
+        };
+        let inner_type = *inner_type;
+        let Some(LayoutNode::Split {
+            split_type,
+            children,
+            ratios,
+        }) = self.node_mut(parent_path)
+        else {
+            return;
+        };
+        if *split_type != inner_type {
+            return;
+        }
+
+        let LayoutNode::Split {
+            children: inner_children,
+            ratios: inner_ratios,
+            ..
+        } = children.remove(index)
+        else {
+            unreachable!()
+        };
+        let share = ratios.remove(index);
+        for (offset, (child, ratio)) in inner_children.into_iter().zip(inner_ratios).enumerate() {
+            children.insert(index + offset, child);
+            ratios.insert(index + offset, ratio * share);
+        }
+    }
+
+    /// Exchange the positions of two panes.
+    pub fn swap(&mut self, a: PaneId, b: PaneId) -> bool {
+        if a == b {
+            return false;
+        }
+        let (Some(path_a), Some(path_b)) = (self.path_of(a), self.path_of(b)) else {
+            return false;
+        };
+        let Some(node_a) = self.node(&path_a).cloned() else {
+            return false;
+        };
+        let Some(node_b) = self.node(&path_b).cloned() else {
+            return false;
+        };
+        if let Some(slot) = self.node_mut(&path_a) {
+            *slot = node_b;
+        }
+        if let Some(slot) = self.node_mut(&path_b) {
+            *slot = node_a;
+        }
+        true
+    }
+
+    /// Grow (or shrink, for a negative `delta`) the share of `target` in the nearest
+    /// enclosing split of the given orientation.
+    pub fn resize(&mut self, target: PaneId, split_type: SplitType, delta: f64) -> bool {
+        let Some(mut path) = self.path_of(target) else {
+            return false;
+        };
+
+        while let Some(index) = path.pop() {
+            let Some(LayoutNode::Split {
+                split_type: st,
+                ratios,
+                ..
+            }) = self.node_mut(&path)
+            else {
+                return false;
+            };
+            if *st != split_type {
+                continue;
+            }
+
+            let other = if index + 1 < ratios.len() {
+                index + 1
+            } else {
+                index - 1
+            };
+            let delta = delta
+                .min(ratios[other] - MIN_RATIO)
+                .max(MIN_RATIO - ratios[index]);
+            if delta.abs() < 1e-9 {
+                return false;
+            }
+            ratios[index] += delta;
+            ratios[other] -= delta;
+            return true;
+        }
+        false
+    }
+
+    /// Where each pane sits according to the split ratios, in reading order.
+    pub fn pane_rects(&self) -> Vec<(PaneId, Rect)> {
+        fn walk(node: &LayoutNode, rect: Rect, out: &mut Vec<(PaneId, Rect)>) {
+            match node {
+                LayoutNode::Pane { id, .. } => out.push((*id, rect)),
+                LayoutNode::Split {
+                    split_type,
+                    children,
+                    ratios,
+                } => {
+                    let mut offset = 0.0;
+                    for (child, ratio) in children.iter().zip(ratios) {
+                        let child_rect = match split_type {
+                            SplitType::Horizontal => Rect {
+                                x: rect.x + offset * rect.width,
+                                width: ratio * rect.width,
+                                ..rect
+                            },
+                            SplitType::Vertical => Rect {
+                                y: rect.y + offset * rect.height,
+                                height: ratio * rect.height,
+                                ..rect
+                            },
+                        };
+                        walk(child, child_rect, out);
+                        offset += ratio;
+                    }
+                }
+            }
+        }
+
+        let mut rects = Vec::new();
+        if let Some(root) = &self.root {
+            let full = Rect {
+                x: 0.0,
+                y: 0.0,
+                width: 1.0,
+                height: 1.0,
+            };
+            walk(root, full, &mut rects);
+        }
+        rects
+    }
+
+    /// The closest pane on the given side of `from`. Among panes that touch it,
+    /// the one sharing the longest edge wins.
+    pub fn neighbour(&self, from: PaneId, direction: Direction) -> Option<PaneId> {
+        const EPSILON: f64 = 1e-6;
+        let rects = self.pane_rects();
+        let (_, a) = *rects.iter().find(|(id, _)| *id == from)?;
+
+        let overlap = |start_a: f64, len_a: f64, start_b: f64, len_b: f64| {
+            (start_a + len_a).min(start_b + len_b) - start_a.max(start_b)
+        };
+
+        rects
+            .iter()
+            .filter(|(id, _)| *id != from)
+            .filter_map(|&(id, b)| {
+                // Distance along the direction of travel, shared edge across it
+                let (gap, shared) = match direction {
+                    Direction::Left => {
+                        (a.x - (b.x + b.width), overlap(a.y, a.height, b.y, b.height))
+                    }
+                    Direction::Right => {
+                        (b.x - (a.x + a.width), overlap(a.y, a.height, b.y, b.height))
+                    }
+                    Direction::Up => (a.y - (b.y + b.height), overlap(a.x, a.width, b.x, b.width)),
+                    Direction::Down => {
+                        (b.y - (a.y + a.height), overlap(a.x, a.width, b.x, b.width))
+                    }
+                };
+                (gap > -EPSILON && shared > EPSILON).then_some((id, gap, shared))
+            })
+            .min_by(|(_, gap_a, shared_a), (_, gap_b, shared_b)| {
+                gap_a.total_cmp(gap_b).then(shared_b.total_cmp(shared_a))
+            })
+            .map(|(id, _, _)| id)
+    }
+
+    /// Ratios of the split at `path`.
+    pub fn ratios_at(&self, path: &[usize]) -> Option<&[f64]> {
+        match self.node(path)? {
+            LayoutNode::Split { ratios, .. } => Some(ratios),
+            LayoutNode::Pane { .. } => None,
+        }
+    }
+
+    /// Overwrite the ratios of the split at `path` (used to sync from dragged dividers).
+    pub fn set_ratios(&mut self, path: &[usize], new_ratios: Vec<f64>) -> bool {
+        match self.node_mut(path) {
+            Some(LayoutNode::Split {
+                children, ratios, ..
+            }) if children.len() == new_ratios.len() => {
+                *ratios = new_ratios;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn path_of(&self, target: PaneId) -> Option<Vec<usize>> {
+        fn find(node: &LayoutNode, target: PaneId, path: &mut Vec<usize>) -> bool {
+            match node {
+                LayoutNode::Pane { id, .. } => *id == target,
+                LayoutNode::Split { children, .. } => {
+                    for (i, child) in children.iter().enumerate() {
+                        path.push(i);
+                        if find(child, target, path) {
+                            return true;
+                        }
+                        path.pop();
+                    }
+                    false
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        find(self.root.as_ref()?, target, &mut path).then_some(path)
+    }
+
+    fn node(&self, path: &[usize]) -> Option<&LayoutNode> {
+        let mut node = self.root.as_ref()?;
+        for &i in path {
+            match node {
+                LayoutNode::Split { children, .. } => node = children.get(i)?,
+                LayoutNode::Pane { .. } => return None,
+            }
+        }
+        Some(node)
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> Option<&mut LayoutNode> {
+        let mut node = self.root.as_mut()?;
+        for &i in path {
+            match node {
+                LayoutNode::Split { children, .. } => node = children.get_mut(i)?,
+                LayoutNode::Pane { .. } => return None,
+            }
+        }
+        Some(node)
+    }
+}
+
+fn collect_ids(node: &LayoutNode, ids: &mut Vec<PaneId>) {
+    match node {
+        LayoutNode::Pane { id, .. } => ids.push(*id),
+        LayoutNode::Split { children, .. } => {
+            for child in children {
+                collect_ids(child, ids);
+            }
+        }
+    }
+}
+
+fn node_from_layout(layout: &TerminalLayout) -> Result<LayoutNode, String> {
+    let Some(split_type) = layout.split_type else {
+        let mut pane = layout.clone();
+        pane.children.clear();
+        return Ok(LayoutNode::Pane {
+            id: PaneId::next(),
+            pane,
+        });
+    };
+
+    match layout.children.len() {
+        0 => return Err("Split must have at least one child".into()),
+        // A split around a single pane is just that pane
+        1 => return node_from_layout(&layout.children[0]),
+        _ => {}
+    }
+
+    let children = layout
+        .children
+        .iter()
+        .map(node_from_layout)
+        .collect::<Result<Vec<_>, _>>()?;
+    let ratios = normalize_ratios(&layout.ratios, children.len());
+
+    Ok(LayoutNode::Split {
+        split_type,
+        children,
+        ratios,
+    })
+}
+
+fn node_to_layout(node: &LayoutNode) -> TerminalLayout {
+    match node {
+        LayoutNode::Pane { pane, .. } => pane.clone(),
+        LayoutNode::Split {
+            split_type,
+            children,
+            ratios,
+        } => TerminalLayout {
+            name: "Split".to_string(),
+            split_type: Some(*split_type),
+            children: children.iter().map(node_to_layout).collect(),
+            ratios: ratios.clone(),
+            ..Default::default()
+        },
+    }
+}
+
+/// Divider positions for a chain of Paneds: each divider takes its child's share of
+/// what is left after the dividers before it.
+pub fn divider_fractions(ratios: &[f64]) -> Vec<f64> {
+    (0..ratios.len().saturating_sub(1))
+        .map(|i| {
+            let remaining: f64 = ratios[i..].iter().sum();
+            // Collapsed trailing panes would make this 0/0
+            if remaining <= f64::EPSILON {
+                1.0
+            } else {
+                (ratios[i] / remaining).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+// Saved ratios are only trusted if there is one positive value per child
+fn normalize_ratios(ratios: &[f64], count: usize) -> Vec<f64> {
+    let valid = ratios.len() == count && ratios.iter().all(|r| r.is_finite() && *r >= 0.0);
+    let total: f64 = ratios.iter().sum();
+    if !valid || total <= 0.0 {
+        return vec![1.0 / count as f64; count];
+    }
+    ratios.iter().map(|r| r / total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pane(name: &str) -> TerminalLayout {
+        TerminalLayout {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn names(tree: &LayoutTree) -> Vec<String> {
+        tree.pane_ids()
+            .into_iter()
+            .map(|id| tree.pane(id).unwrap().name.clone())
+            .collect()
+    }
+
+    fn root_ratios(tree: &LayoutTree) -> Vec<f64> {
+        match tree.root() {
+            Some(LayoutNode::Split { ratios, .. }) => ratios.clone(),
+            _ => panic!("root is not a split"),
+        }
+    }
+
+    #[test]
+    fn split_nests_new_pane_after_target() {
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        let b = tree.split(a, SplitType::Horizontal, pane("b")).unwrap();
+        let c = tree.split(a, SplitType::Vertical, pane("c")).unwrap();
+
+        assert_eq!(tree.pane_ids(), vec![a, c, b]);
+        let layout = tree.to_layout().unwrap();
+        assert_eq!(layout.split_type, Some(SplitType::Horizontal));
+        assert_eq!(layout.children[0].split_type, Some(SplitType::Vertical));
+        assert_eq!(layout.children[1].name, "b");
+    }
+
+    #[test]
+    fn split_in_same_orientation_adds_sibling() {
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        let b = tree.split(a, SplitType::Horizontal, pane("b")).unwrap();
+        let c = tree.split(a, SplitType::Horizontal, pane("c")).unwrap();
+
+        assert_eq!(tree.pane_ids(), vec![a, c, b]);
+        let layout = tree.to_layout().unwrap();
+        assert_eq!(layout.children.len(), 3);
+        assert!(layout.children.iter().all(|c| c.split_type.is_none()));
+        assert_eq!(root_ratios(&tree), vec![0.25, 0.25, 0.5]);
+    }
+
+    #[test]
+    fn split_unknown_pane_is_rejected() {
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        tree.close(a);
+        assert!(tree.split(a, SplitType::Horizontal, pane("b")).is_none());
+    }
+
+    #[test]
+    fn close_collapses_single_child_split() {
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        let b = tree.split(a, SplitType::Horizontal, pane("b")).unwrap();
+        let c = tree.split(b, SplitType::Vertical, pane("c")).unwrap();
+
+        assert!(tree.close(b));
+        assert_eq!(names(&tree), vec!["a", "c"]);
+        let layout = tree.to_layout().unwrap();
+        assert_eq!(layout.split_type, Some(SplitType::Horizontal));
+        assert_eq!(layout.children[1].split_type, None);
+
+        assert!(tree.close(a));
+        assert_eq!(tree.pane_ids(), vec![c]);
+        assert!(tree.close(c));
+        assert!(tree.is_empty());
+        assert!(!tree.close(c));
+    }
+
+    #[test]
+    fn close_merges_nested_split_of_same_orientation() {
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        let b = tree.split(a, SplitType::Horizontal, pane("b")).unwrap();
+        let c = tree.split(b, SplitType::Vertical, pane("c")).unwrap();
+        tree.split(c, SplitType::Horizontal, pane("d")).unwrap();
+
+        // [a | [b / [c | d]]] -> [a | c | d]
+        assert!(tree.close(b));
+        assert_eq!(names(&tree), vec!["a", "c", "d"]);
+        assert_eq!(tree.to_layout().unwrap().children.len(), 3);
+        assert_eq!(root_ratios(&tree), vec![0.5, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn close_in_nary_split_gives_space_to_neighbour() {
+        let layout = TerminalLayout {
+            split_type: Some(SplitType::Horizontal),
+            children: vec![pane("a"), pane("b"), pane("c")],
+            ratios: vec![0.2, 0.3, 0.5],
+            ..Default::default()
+        };
+        let mut tree = LayoutTree::from_layout(&layout).unwrap();
+        let ids = tree.pane_ids();
+
+        assert!(tree.close(ids[1]));
+        assert_eq!(names(&tree), vec!["a", "c"]);
+        assert_eq!(root_ratios(&tree), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn swap_exchanges_panes() {
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        let b = tree.split(a, SplitType::Horizontal, pane("b")).unwrap();
+        let c = tree.split(b, SplitType::Vertical, pane("c")).unwrap();
+
+        assert!(tree.swap(a, c));
+        assert_eq!(tree.pane_ids(), vec![c, b, a]);
+        assert!(!tree.swap(a, a));
+    }
+
+    #[test]
+    fn resize_targets_split_with_matching_orientation() {
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        let b = tree.split(a, SplitType::Horizontal, pane("b")).unwrap();
+        tree.split(b, SplitType::Vertical, pane("c")).unwrap();
+
+        assert!(tree.resize(b, SplitType::Horizontal, -0.2));
+        let ratios = root_ratios(&tree);
+        assert!((ratios[0] - 0.7).abs() < 1e-9);
+        assert!((ratios[1] - 0.3).abs() < 1e-9);
+
+        // Clamped so neither side disappears
+        assert!(tree.resize(a, SplitType::Horizontal, 5.0));
+        let ratios = root_ratios(&tree);
+        assert!((ratios[1] - MIN_RATIO).abs() < 1e-9);
+        assert!(!tree.resize(a, SplitType::Horizontal, 0.1));
+
+        // No vertical split encloses `a`
+        assert!(!tree.resize(a, SplitType::Vertical, 0.1));
+    }
+
+    #[test]
+    fn layout_round_trip() {
+        let layout = TerminalLayout {
+            name: "Split".into(),
+            split_type: Some(SplitType::Vertical),
+            children: vec![
+                TerminalLayout {
+                    name: "Editor".into(),
+                    color_index: 3,
+                    working_dir: "/tmp".into(),
+                    ..Default::default()
+                },
+                pane("Logs"),
+            ],
+            ..Default::default()
+        };
+
+        let tree = LayoutTree::from_layout(&layout).unwrap();
+        assert_eq!(names(&tree), vec!["Editor", "Logs"]);
+
+        let back = tree.to_layout().unwrap();
+        assert_eq!(back.split_type, Some(SplitType::Vertical));
+        assert_eq!(back.children[0].color_index, 3);
+        assert_eq!(back.children[0].working_dir, "/tmp");
+    }
+
+    #[test]
+    fn ratios_survive_round_trip() {
+        let layout = TerminalLayout {
+            split_type: Some(SplitType::Horizontal),
+            children: vec![pane("narrow"), pane("wide")],
+            ratios: vec![0.25, 0.75],
+            ..Default::default()
+        };
+
+        let tree = LayoutTree::from_layout(&layout).unwrap();
+        assert_eq!(root_ratios(&tree), vec![0.25, 0.75]);
+        assert_eq!(tree.to_layout().unwrap().ratios, vec![0.25, 0.75]);
+    }
+
+    #[test]
+    fn missing_or_invalid_ratios_fall_back_to_even_split() {
+        // A version 1.0 file has no ratios at all
+        let json = r#"{
+            "name": "Split", "color_index": 0, "working_dir": "",
+            "split_type": "Vertical",
+            "children": [
+                {"name": "a", "color_index": 0, "working_dir": "", "split_type": null, "children": []},
+                {"name": "b", "color_index": 0, "working_dir": "", "split_type": null, "children": []}
+            ]
+        }"#;
+        let layout: TerminalLayout = serde_json::from_str(json).unwrap();
+        let tree = LayoutTree::from_layout(&layout).unwrap();
+        assert_eq!(root_ratios(&tree), vec![0.5, 0.5]);
+
+        let mut layout = layout;
+        layout.ratios = vec![-1.0, 2.0];
+        let tree = LayoutTree::from_layout(&layout).unwrap();
+        assert_eq!(root_ratios(&tree), vec![0.5, 0.5]);
+
+        // Values that don't add up to 1 are scaled
+        layout.ratios = vec![1.0, 3.0];
+        let tree = LayoutTree::from_layout(&layout).unwrap();
+        assert_eq!(root_ratios(&tree), vec![0.25, 0.75]);
+    }
+
+    #[test]
+    fn zero_ratios_give_finite_dividers() {
+        assert_eq!(divider_fractions(&[0.25, 0.25, 0.5]), vec![0.25, 1.0 / 3.0]);
+        // A pane collapsed to nothing, as sync_ratios can save it
+        let layout = TerminalLayout {
+            split_type: Some(SplitType::Horizontal),
+            children: vec![pane("a"), pane("b"), pane("c")],
+            ratios: vec![1.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        let tree = LayoutTree::from_layout(&layout).unwrap();
+        let fractions = divider_fractions(&root_ratios(&tree));
+        assert_eq!(fractions, vec![1.0, 1.0]);
+        assert!(divider_fractions(&[0.0, 0.0]).iter().all(|f| f.is_finite()));
+    }
+
+    #[test]
+    fn from_layout_accepts_any_number_of_children() {
+        let layout = TerminalLayout {
+            split_type: Some(SplitType::Horizontal),
+            children: vec![pane("api"), pane("web"), pane("logs")],
+            ..Default::default()
+        };
+        let tree = LayoutTree::from_layout(&layout).unwrap();
+        assert_eq!(names(&tree), vec!["api", "web", "logs"]);
+        assert_eq!(root_ratios(&tree).len(), 3);
+
+        let single = TerminalLayout {
+            split_type: Some(SplitType::Vertical),
+            children: vec![pane("only")],
+            ..Default::default()
+        };
+        let tree = LayoutTree::from_layout(&single).unwrap();
+        assert!(matches!(tree.root(), Some(LayoutNode::Pane { .. })));
+
+        let empty = TerminalLayout {
+            split_type: Some(SplitType::Vertical),
+            ..Default::default()
+        };
+        assert!(LayoutTree::from_layout(&empty).is_err());
+    }
+
+    #[test]
+    fn pane_rects_follow_ratios() {
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        let b = tree.split(a, SplitType::Horizontal, pane("b")).unwrap();
+        let c = tree.split(b, SplitType::Vertical, pane("c")).unwrap();
+        assert!(tree.resize(a, SplitType::Horizontal, -0.25));
+
+        let rects = tree.pane_rects();
+        let rect = |id| rects.iter().find(|(i, _)| *i == id).unwrap().1;
+        assert!((rect(a).width - 0.25).abs() < 1e-9);
+        assert!((rect(b).x - 0.25).abs() < 1e-9);
+        assert!((rect(c).y - 0.5).abs() < 1e-9);
+        assert!((rect(c).height - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn neighbour_is_found_geometrically() {
+        // +---+---+
+        // | a | b |
+        // |   +---+
+        // |   | c |
+        // +---+---+
+        // |   d   |
+        // +-------+
+        let (mut tree, a) = LayoutTree::with_pane(pane("a"));
+        let d = tree.split(a, SplitType::Vertical, pane("d")).unwrap();
+        let b = tree.split(a, SplitType::Horizontal, pane("b")).unwrap();
+        let c = tree.split(b, SplitType::Vertical, pane("c")).unwrap();
+
+        assert_eq!(tree.neighbour(a, Direction::Right), Some(b));
+        assert_eq!(tree.neighbour(c, Direction::Left), Some(a));
+        assert_eq!(tree.neighbour(b, Direction::Down), Some(c));
+        assert_eq!(tree.neighbour(c, Direction::Down), Some(d));
+        assert_eq!(tree.neighbour(a, Direction::Down), Some(d));
+        assert_eq!(tree.neighbour(a, Direction::Left), None);
+        assert_eq!(tree.neighbour(d, Direction::Down), None);
+
+        // d touches a and c equally; a comes first
+        assert_eq!(tree.neighbour(d, Direction::Up), Some(a));
+        assert!(tree.resize(a, SplitType::Horizontal, -0.2));
+        assert_eq!(tree.neighbour(d, Direction::Up), Some(c));
+    }
+}
@@ -1,23 +1,423 @@
+use std::collections::{BTreeMap, HashSet};
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Written into saved layouts and sessions
+pub const LAYOUT_VERSION: &str = "2.2";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TerminalLayout {
     pub name: String,
+    // Leaves only: stable pane UUID, also given to the shell as $SPLITERMA_PANE_ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    // Leaves only: title bar accent as #RRGGBB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    // Files before 2.2: index into the accent palette, used when `color` is missing
+    #[serde(default, skip_serializing)]
     pub color_index: usize,
     pub working_dir: String,
     pub split_type: Option<SplitType>,
     pub children: Vec<TerminalLayout>,
+    // Splits only: share of the space given to each child, in order (missing = even split)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ratios: Vec<f64>,
+    // Leaves only: program (argv) to start instead of the interactive shell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+    // Leaves only: environment variables set on top of the inherited environment
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    // Leaves only: drop into the interactive shell once `command` exits
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keep_shell: bool,
+    // Leaves only: colour scheme id overriding terminal.color_scheme from the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_scheme: Option<String>,
+    // Leaves only: zoom factor on top of the configured font (missing = 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_scale: Option<f64>,
+    // Leaves only: append the pane's output, timestamped, to this file (~ and relative paths allowed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    // Leaves only: overrides terminal.scrollback_lines from the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrollback_lines: Option<Scrollback>,
+    // Panes with the same group mirror each other's input in group broadcast mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broadcast_group: Option<String>,
+    // Leaves only: what happens when the process exits (overrides terminal.on_exit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_exit: Option<ExitPolicy>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What a pane does once its shell or command exits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExitPolicy {
+    // Close the pane, and the tab with its last pane
+    #[default]
+    Close,
+    // Keep the output and show the exit status with a Restart button
+    Hold,
+    // Start the command again
+    Restart,
+}
+
+impl ExitPolicy {
+    pub const ALL: [ExitPolicy; 3] = [ExitPolicy::Close, ExitPolicy::Hold, ExitPolicy::Restart];
+
+    /// Same word as in layout files and the config.
+    pub fn id(self) -> &'static str {
+        match self {
+            ExitPolicy::Close => "close",
+            ExitPolicy::Hold => "hold",
+            ExitPolicy::Restart => "restart",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|policy| policy.id() == id)
+    }
+}
+
+/// Scrollback size: a number of lines or "unlimited".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "ScrollbackRepr", into = "ScrollbackRepr")]
+pub enum Scrollback {
+    Lines(u32),
+    Unlimited,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ScrollbackRepr {
+    Lines(u32),
+    Word(String),
+}
+
+impl TryFrom<ScrollbackRepr> for Scrollback {
+    type Error = String;
+
+    fn try_from(repr: ScrollbackRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ScrollbackRepr::Lines(lines) => Ok(Scrollback::Lines(lines)),
+            ScrollbackRepr::Word(word) if word == "unlimited" => Ok(Scrollback::Unlimited),
+            ScrollbackRepr::Word(word) => Err(format!(
+                "scrollback must be a number of lines or \"unlimited\", got \"{word}\""
+            )),
+        }
+    }
+}
+
+impl From<Scrollback> for ScrollbackRepr {
+    fn from(scrollback: Scrollback) -> Self {
+        match scrollback {
+            Scrollback::Lines(lines) => ScrollbackRepr::Lines(lines),
+            Scrollback::Unlimited => ScrollbackRepr::Word("unlimited".into()),
+        }
+    }
+}
+
+impl Scrollback {
+    /// Value for `Terminal::set_scrollback_lines`, where -1 means unlimited.
+    pub fn vte_lines(self) -> i64 {
+        match self {
+            Scrollback::Lines(lines) => lines as i64,
+            Scrollback::Unlimited => -1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub enum SplitType {
     Horizontal,
     Vertical,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabLayout {
+    pub title: String,
+    pub root: TerminalLayout,
+    // Id of the pane that had focus last
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focused: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowLayout {
+    // [width, height] of the unmaximized window; positions can't be restored on Wayland
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<[i32; 2]>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub maximized: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fullscreen: bool,
+    pub tabs: Vec<TabLayout>,
+    #[serde(default)]
+    pub active_tab: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SavedLayout {
     pub version: String,
-    pub root: TerminalLayout,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<WindowLayout>,
+    // 2.0 files: the tabs of a single window
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tabs: Vec<TabLayout>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub active_tab: usize,
+    // 1.x files: a single pane tree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<TerminalLayout>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl SavedLayout {
+    pub fn new(windows: Vec<WindowLayout>) -> Self {
+        SavedLayout {
+            version: LAYOUT_VERSION.into(),
+            windows,
+            tabs: Vec::new(),
+            active_tab: 0,
+            root: None,
+        }
+    }
+
+    /// The windows described by the file. Older formats describe a single window.
+    /// Pane ids are unique across all of them.
+    pub fn windows(&self) -> Vec<WindowLayout> {
+        let mut windows = self.described_windows();
+        dedupe_pane_ids(&mut windows);
+        windows
+    }
+
+    fn described_windows(&self) -> Vec<WindowLayout> {
+        if !self.windows.is_empty() {
+            return self.windows.clone();
+        }
+
+        let tabs = match &self.root {
+            Some(root) if self.tabs.is_empty() => vec![TabLayout {
+                title: "Tab 1".into(),
+                root: root.clone(),
+                focused: None,
+            }],
+            _ => self.tabs.clone(),
+        };
+        if tabs.is_empty() {
+            return Vec::new();
+        }
+        vec![WindowLayout {
+            tabs,
+            active_tab: self.active_tab,
+            ..Default::default()
+        }]
+    }
+}
+
+// Every tab's workspace is built before any is in a window, so the panes can't
+// check each other: drop ids an earlier pane already has and let them get new ones
+fn dedupe_pane_ids(windows: &mut [WindowLayout]) {
+    let mut seen = HashSet::new();
+    for tab in windows.iter_mut().flat_map(|w| w.tabs.iter_mut()) {
+        let mut kept = Vec::new();
+        dedupe_tree(&mut tab.root, &mut seen, &mut kept);
+        // The focused pane may have been one of the copies
+        if tab.focused.as_ref().is_some_and(|id| !kept.contains(id)) {
+            tab.focused = None;
+        }
+    }
+}
+
+fn dedupe_tree(node: &mut TerminalLayout, seen: &mut HashSet<String>, kept: &mut Vec<String>) {
+    if let Some(id) = node.id.take() {
+        if seen.insert(id.clone()) {
+            kept.push(id.clone());
+            node.id = Some(id);
+        }
+    }
+    for child in &mut node.children {
+        dedupe_tree(child, seen, kept);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tab(title: &str) -> TabLayout {
+        TabLayout {
+            title: title.into(),
+            root: TerminalLayout::default(),
+            focused: None,
+        }
+    }
+
+    #[test]
+    fn single_tree_files_load_as_one_tab() {
+        let json = r#"{
+            "version": "1.0",
+            "root": {"name": "Terminal 1", "color_index": 2, "working_dir": "/tmp",
+                     "split_type": null, "children": []}
+        }"#;
+        let saved: SavedLayout = serde_json::from_str(json).unwrap();
+        let windows = saved.windows();
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].tabs.len(), 1);
+        assert_eq!(windows[0].tabs[0].root.color_index, 2);
+        assert_eq!(windows[0].size, None);
+    }
+
+    #[test]
+    fn tab_files_load_as_one_window() {
+        let json = r#"{
+            "version": "2.0",
+            "tabs": [
+                {"title": "A", "root": {"name": "a", "color_index": 0, "working_dir": "",
+                                        "split_type": null, "children": []}},
+                {"title": "B", "root": {"name": "b", "color_index": 0, "working_dir": "",
+                                        "split_type": null, "children": []}}
+            ],
+            "active_tab": 1
+        }"#;
+        let saved: SavedLayout = serde_json::from_str(json).unwrap();
+        let windows = saved.windows();
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].tabs.len(), 2);
+        assert_eq!(windows[0].active_tab, 1);
+    }
+
+    #[test]
+    fn scrollback_lines_or_unlimited() {
+        let json = r#"[{"name": "a", "working_dir": "", "split_type": null, "children": [],
+                        "scrollback_lines": "unlimited"},
+                       {"name": "b", "working_dir": "", "split_type": null, "children": [],
+                        "scrollback_lines": 5000}]"#;
+        let panes: Vec<TerminalLayout> = serde_json::from_str(json).unwrap();
+        assert_eq!(panes[0].scrollback_lines, Some(Scrollback::Unlimited));
+        assert_eq!(panes[1].scrollback_lines, Some(Scrollback::Lines(5000)));
+        assert_eq!(Scrollback::Unlimited.vte_lines(), -1);
+
+        let back = serde_json::to_string(&panes[0]).unwrap();
+        assert!(back.contains(r#""scrollback_lines":"unlimited""#));
+        let bad = r#"{"name": "a", "working_dir": "", "split_type": null, "children": [],
+                      "scrollback_lines": "lots"}"#;
+        assert!(serde_json::from_str::<TerminalLayout>(bad).is_err());
+    }
+
+    #[test]
+    fn exit_policies() {
+        let json = r#"[{"name": "a", "working_dir": "", "split_type": null, "children": [],
+                        "on_exit": "restart"},
+                       {"name": "b", "working_dir": "", "split_type": null, "children": []}]"#;
+        let panes: Vec<TerminalLayout> = serde_json::from_str(json).unwrap();
+        assert_eq!(panes[0].on_exit, Some(ExitPolicy::Restart));
+        assert_eq!(panes[1].on_exit, None);
+        assert_eq!(ExitPolicy::from_id("hold"), Some(ExitPolicy::Hold));
+        assert!(
+            !serde_json::to_string(&panes[1])
+                .unwrap()
+                .contains("on_exit")
+        );
+        assert!(serde_json::from_str::<ExitPolicy>("\"linger\"").is_err());
+    }
+
+    #[test]
+    fn pane_ids_are_unique_across_tabs_and_windows() {
+        let leaf = |id: &str| TerminalLayout {
+            id: Some(id.into()),
+            ..Default::default()
+        };
+        let split = |children| TerminalLayout {
+            split_type: Some(SplitType::Horizontal),
+            children,
+            ..Default::default()
+        };
+        let with = |root, focused: &str| TabLayout {
+            root,
+            focused: Some(focused.into()),
+            ..tab("t")
+        };
+        let saved = SavedLayout::new(vec![
+            WindowLayout {
+                tabs: vec![
+                    with(split(vec![leaf("a"), leaf("b")]), "b"),
+                    with(split(vec![leaf("a"), leaf("c")]), "a"),
+                ],
+                ..Default::default()
+            },
+            WindowLayout {
+                tabs: vec![with(leaf("c"), "c")],
+                ..Default::default()
+            },
+        ]);
+
+        let windows = saved.windows();
+        let first = &windows[0].tabs;
+        let ids = |tab: &TabLayout| -> Vec<Option<String>> {
+            tab.root.children.iter().map(|c| c.id.clone()).collect()
+        };
+        assert_eq!(ids(&first[0]), [Some("a".into()), Some("b".into())]);
+        // The copies get new ids when their panes are created
+        assert_eq!(ids(&first[1]), [None, Some("c".into())]);
+        assert_eq!(windows[1].tabs[0].root.id, None);
+        // Focus only stays on a pane that kept its id
+        assert_eq!(first[0].focused.as_deref(), Some("b"));
+        assert_eq!(first[1].focused, None);
+        assert_eq!(windows[1].tabs[0].focused, None);
+    }
+
+    #[test]
+    fn pane_ids_and_focus_are_optional() {
+        let json = r#"{"title": "t", "focused": "0b5c3f3e-5d1a-4c1e-9f57-1f1c2a6c8d2e",
+                       "root": {"name": "a", "id": "0b5c3f3e-5d1a-4c1e-9f57-1f1c2a6c8d2e",
+                                "working_dir": "", "split_type": null, "children": []}}"#;
+        let restored: TabLayout = serde_json::from_str(json).unwrap();
+        assert_eq!(restored.focused, restored.root.id);
+        assert!(restored.root.id.is_some());
+
+        // Layouts from before pane ids still load
+        let old: TabLayout = serde_json::from_str(
+            r#"{"title": "t", "root": {"name": "a", "working_dir": "", "split_type": null, "children": []}}"#,
+        )
+        .unwrap();
+        assert_eq!((old.focused, old.root.id), (None, None));
+        assert!(!serde_json::to_string(&tab("x")).unwrap().contains("\"id\""));
+    }
+
+    #[test]
+    fn windows_round_trip() {
+        let saved = SavedLayout::new(vec![
+            WindowLayout {
+                size: Some([1200, 800]),
+                tabs: vec![tab("Servers"), tab("Logs")],
+                active_tab: 1,
+                ..Default::default()
+            },
+            WindowLayout {
+                maximized: true,
+                tabs: vec![tab("Editor")],
+                ..Default::default()
+            },
+        ]);
+        let json = serde_json::to_string(&saved).unwrap();
+        let back: SavedLayout = serde_json::from_str(&json).unwrap();
+
+        assert!(back.root.is_none() && back.tabs.is_empty());
+        let windows = back.windows();
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].size, Some([1200, 800]));
+        assert_eq!(windows[0].active_tab, 1);
+        assert!(windows[1].maximized);
+        assert!(!json.contains("color_index"));
+        let titles: Vec<_> = windows[0].tabs.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Servers", "Logs"]);
+    }
 }
@@ -0,0 +1,8 @@
+pub mod app;
+pub mod cli;
+pub mod config;
+pub mod constants;
+pub mod layout;
+pub mod remote;
+pub mod ui;
+pub mod util;
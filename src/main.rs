@@ -1,9 +1,3 @@
-mod app;
-mod constants;
-mod layout;
-mod ui;
-mod util;
-
 fn main() {
-    app::run();
+    spliterma::app::run();
 }
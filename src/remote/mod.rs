@@ -0,0 +1,325 @@
+use std::path::Path;
+
+use gtk::gio;
+use gtk4 as gtk;
+use vte4::prelude::*;
+
+use crate::app::{find_focused_terminal, open_saved_windows};
+use crate::layout::extract::pane_layout;
+use crate::layout::persist::{load_into, read_layout_file, write_layout_file};
+use crate::layout::tree::PaneId;
+use crate::ui::broadcast::feed_unmirrored;
+use crate::ui::split::{next_pane_layout, stop_terminal};
+use crate::ui::tabs::{window_notebook, workspaces};
+use crate::ui::terminal::{child_pid, find_terminal_in_container, set_pane_color, set_pane_title};
+use crate::ui::workspace::{Workspace, pane_container_of, pane_id_of, pane_uuid};
+
+// GApplication owns the app id on the session bus and exports its own
+// interfaces on the matching path; ours goes next to them
+pub const BUS_NAME: &str = "com.spliterma.app";
+pub const OBJECT_PATH: &str = "/com/spliterma/app";
+pub const INTERFACE: &str = "com.spliterma.app.Remote";
+pub const ERROR_FAILED: &str = "com.spliterma.app.Remote.Error.Failed";
+// Set in every pane's environment to the pane's id
+pub const PANE_ID_ENV: &str = "SPLITERMA_PANE_ID";
+
+// An empty pane argument means the focused pane of the active window
+pub const INTROSPECTION_XML: &str = r#"
+<node>
+  <interface name="com.spliterma.app.Remote">
+    <method name="ListPanes">
+      <!-- id, name, working directory, colour, pid (0 = not started), window, tab -->
+      <arg type="a(ssssiuu)" name="panes" direction="out"/>
+    </method>
+    <method name="Split">
+      <arg type="s" name="pane" direction="in"/>
+      <arg type="b" name="vertical" direction="in"/>
+      <arg type="as" name="command" direction="in"/>
+      <arg type="s" name="new_pane" direction="out"/>
+    </method>
+    <method name="SendText">
+      <arg type="s" name="pane" direction="in"/>
+      <arg type="s" name="text" direction="in"/>
+    </method>
+    <method name="Focus">
+      <arg type="s" name="pane" direction="in"/>
+    </method>
+    <method name="Rename">
+      <arg type="s" name="pane" direction="in"/>
+      <arg type="s" name="name" direction="in"/>
+    </method>
+    <method name="SetColor">
+      <arg type="s" name="pane" direction="in"/>
+      <arg type="s" name="color" direction="in"/>
+    </method>
+    <method name="Close">
+      <arg type="s" name="pane" direction="in"/>
+    </method>
+    <method name="SaveLayout">
+      <arg type="s" name="path" direction="in"/>
+    </method>
+    <method name="LoadLayout">
+      <arg type="s" name="path" direction="in"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+// Where a pane lives, found by walking the app's windows and tabs
+struct PaneRef {
+    window: gtk::ApplicationWindow,
+    workspace: Workspace,
+    id: PaneId,
+    container: gtk::Box,
+    window_index: u32,
+    tab_index: u32,
+}
+
+/// Register the remote control interface on the app's bus connection.
+/// Only the primary instance gets here, so there is one server per session.
+pub fn export(app: &gtk::Application) {
+    let Some(connection) = app.dbus_connection() else {
+        eprintln!("Not on D-Bus, remote control is off");
+        return;
+    };
+    let interface = gio::DBusNodeInfo::for_xml(INTROSPECTION_XML)
+        .ok()
+        .and_then(|node| node.lookup_interface(INTERFACE))
+        .expect("remote control interface XML is valid");
+
+    let app_weak = app.downgrade();
+    let registered = connection
+        .register_object(OBJECT_PATH, &interface)
+        .method_call(move |_, _, _, _, method, params, invocation| {
+            let Some(app) = app_weak.upgrade() else {
+                invocation.return_dbus_error(ERROR_FAILED, "Spliterma is shutting down");
+                return;
+            };
+            match handle(&app, method, &params) {
+                Ok(value) => invocation.return_value(value.as_ref()),
+                Err(e) => {
+                    eprintln!("Remote {method} failed: {e}");
+                    invocation.return_dbus_error(ERROR_FAILED, &e);
+                }
+            }
+        })
+        .build();
+    if let Err(e) = registered {
+        eprintln!("Could not export the remote control interface: {e}");
+    }
+}
+
+fn handle(
+    app: &gtk::Application,
+    method: &str,
+    params: &glib::Variant,
+) -> Result<Option<glib::Variant>, String> {
+    match method {
+        "ListPanes" => Ok(Some((list_panes(app),).to_variant())),
+        "Split" => {
+            let (pane, vertical, command) = args::<(String, bool, Vec<String>)>(params)?;
+            let target = find_pane(app, &pane)?;
+            let mut layout = next_pane_layout();
+            layout.command = Some(command).filter(|c| !c.is_empty());
+            let orientation = if vertical {
+                gtk::Orientation::Vertical
+            } else {
+                gtk::Orientation::Horizontal
+            };
+            let uuid = target
+                .workspace
+                .split(target.id, orientation, layout)
+                .and_then(|id| target.workspace.container(id))
+                .and_then(|c| pane_uuid(&c))
+                .ok_or("Could not split the pane")?;
+            Ok(Some((uuid,).to_variant()))
+        }
+        "SendText" => {
+            let (pane, text) = args::<(String, String)>(params)?;
+            // Typed by a script, not the user: don't broadcast it
+            feed_unmirrored(&terminal_of(&find_pane(app, &pane)?)?, text.as_bytes());
+            Ok(None)
+        }
+        "Focus" => {
+            let (pane,) = args::<(String,)>(params)?;
+            let target = find_pane(app, &pane)?;
+            if let Some(notebook) = window_notebook(&target.window) {
+                notebook.set_current_page(notebook.page_num(target.workspace.widget()));
+            }
+            target.window.present();
+            target.workspace.focus(target.id);
+            Ok(None)
+        }
+        "Rename" => {
+            let (pane, name) = args::<(String, String)>(params)?;
+            if name.is_empty() {
+                return Err("Pane name can't be empty".into());
+            }
+            set_pane_title(&find_pane(app, &pane)?.container, &name)?;
+            Ok(None)
+        }
+        "SetColor" => {
+            let (pane, color) = args::<(String, String)>(params)?;
+            set_pane_color(&find_pane(app, &pane)?.container, &color)?;
+            Ok(None)
+        }
+        "Close" => {
+            let (pane,) = args::<(String,)>(params)?;
+            let target = find_pane(app, &pane)?;
+            stop_terminal(&terminal_of(&target)?, &target.container);
+            Ok(None)
+        }
+        "SaveLayout" => {
+            let (path,) = args::<(String,)>(params)?;
+            let window = active_window(app).ok_or("No window to save")?;
+            write_layout_file(&window, Path::new(&path))?;
+            println!("Layout saved to: {path}");
+            Ok(None)
+        }
+        "LoadLayout" => {
+            let (path,) = args::<(String,)>(params)?;
+            let saved = read_layout_file(Path::new(&path))?;
+            match active_window(app) {
+                Some(window) => load_into(&window, &saved)?,
+                None => open_saved_windows(app, &saved)?,
+            }
+            println!("Layout loaded from: {path}");
+            Ok(None)
+        }
+        _ => Err(format!("Unknown method {method}")),
+    }
+}
+
+// GDBus already checked the signature against the introspection data
+fn args<T: glib::variant::FromVariant>(params: &glib::Variant) -> Result<T, String> {
+    params
+        .get::<T>()
+        .ok_or_else(|| format!("Unexpected arguments {}", params.type_()))
+}
+
+fn active_window(app: &gtk::Application) -> Option<gtk::ApplicationWindow> {
+    app.active_window()
+        .and_then(|w| w.downcast::<gtk::ApplicationWindow>().ok())
+}
+
+fn terminal_of(pane: &PaneRef) -> Result<vte4::Terminal, String> {
+    find_terminal_in_container(&pane.container).ok_or_else(|| "Pane has no terminal".into())
+}
+
+fn all_panes(app: &gtk::Application) -> Vec<PaneRef> {
+    let mut panes = Vec::new();
+    let windows = app
+        .windows()
+        .into_iter()
+        .filter_map(|w| w.downcast::<gtk::ApplicationWindow>().ok());
+    for (window_index, window) in windows.enumerate() {
+        let Some(notebook) = window_notebook(&window) else {
+            continue;
+        };
+        for (tab_index, workspace) in workspaces(&notebook).into_iter().enumerate() {
+            for id in workspace.pane_ids() {
+                if let Some(container) = workspace.container(id) {
+                    panes.push(PaneRef {
+                        window: window.clone(),
+                        workspace: workspace.clone(),
+                        id,
+                        container,
+                        window_index: window_index as u32,
+                        tab_index: tab_index as u32,
+                    });
+                }
+            }
+        }
+    }
+    panes
+}
+
+fn list_panes(app: &gtk::Application) -> Vec<(String, String, String, String, i32, u32, u32)> {
+    all_panes(app)
+        .iter()
+        .map(|pane| {
+            let layout = pane_layout(&pane.container);
+            let pid = find_terminal_in_container(&pane.container)
+                .and_then(|t| child_pid(&t))
+                .unwrap_or(0);
+            (
+                layout.id.unwrap_or_default(),
+                layout.name,
+                layout.working_dir,
+                layout.color.unwrap_or_default(),
+                pid,
+                pane.window_index,
+                pane.tab_index,
+            )
+        })
+        .collect()
+}
+
+fn find_pane(app: &gtk::Application, query: &str) -> Result<PaneRef, String> {
+    if query.is_empty() {
+        return focused_pane(app);
+    }
+    let mut panes = all_panes(app);
+    let names: Vec<(String, String)> = panes
+        .iter()
+        .map(|p| {
+            let layout = pane_layout(&p.container);
+            (layout.id.unwrap_or_default(), layout.name)
+        })
+        .collect();
+    let index = resolve_pane(query, &names)?;
+    Ok(panes.swap_remove(index))
+}
+
+fn focused_pane(app: &gtk::Application) -> Result<PaneRef, String> {
+    let container = active_window(app)
+        .and_then(|w| find_focused_terminal(&w))
+        .and_then(|t| pane_container_of(&t))
+        .ok_or("No focused pane")?;
+    let id = pane_id_of(&container);
+    all_panes(app)
+        .into_iter()
+        .find(|p| Some(p.id) == id)
+        .ok_or_else(|| "No focused pane".into())
+}
+
+/// Index of the pane `query` refers to: an id, or else a title only one pane has.
+pub fn resolve_pane(query: &str, panes: &[(String, String)]) -> Result<usize, String> {
+    if let Some(index) = panes.iter().position(|(id, _)| id == query) {
+        return Ok(index);
+    }
+    let mut named = panes
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, name))| name == query);
+    match (named.next(), named.next()) {
+        (Some((index, _)), None) => Ok(index),
+        (Some(_), Some(_)) => Err(format!(
+            "More than one pane is called '{query}', use its id"
+        )),
+        (None, _) => Err(format!("No pane '{query}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panes_by_id_or_title() {
+        let panes: Vec<(String, String)> = [("1", "api"), ("2", "db"), ("3", "db"), ("4", "3")]
+            .iter()
+            .map(|(id, name)| (id.to_string(), name.to_string()))
+            .collect();
+        assert_eq!(resolve_pane("2", &panes), Ok(1));
+        assert_eq!(resolve_pane("api", &panes), Ok(0));
+        // Ids win over titles
+        assert_eq!(resolve_pane("3", &panes), Ok(2));
+        assert!(
+            resolve_pane("db", &panes)
+                .unwrap_err()
+                .contains("More than one")
+        );
+        assert!(resolve_pane("web", &panes).is_err());
+    }
+}
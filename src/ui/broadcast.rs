@@ -0,0 +1,335 @@
+use std::cell::Cell;
+
+use gtk::gio;
+use gtk4 as gtk;
+use vte4::Terminal;
+use vte4::prelude::*;
+
+use crate::ui::tabs::{window_notebook, workspaces};
+use crate::ui::terminal::find_terminal_in_container;
+use crate::ui::workspace::{Workspace, pane_container_of};
+
+/// Which panes see what is typed into the focused one. Each window has its own
+/// mode, and input never crosses into other windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BroadcastMode {
+    #[default]
+    Off,
+    // Every pane of the window (not of other windows)
+    All,
+    // Panes in the same tab as the one typed into
+    Tab,
+    // Panes of the window with the same broadcast group
+    Group,
+}
+
+impl BroadcastMode {
+    pub fn id(self) -> &'static str {
+        match self {
+            BroadcastMode::Off => "off",
+            BroadcastMode::All => "all",
+            BroadcastMode::Tab => "tab",
+            BroadcastMode::Group => "group",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        [
+            BroadcastMode::Off,
+            BroadcastMode::All,
+            BroadcastMode::Tab,
+            BroadcastMode::Group,
+        ]
+        .into_iter()
+        .find(|mode| mode.id() == id)
+    }
+}
+
+// Kept on the window; `last` is what the toggle switches back on
+#[derive(Clone, Copy, Default)]
+struct BroadcastState {
+    mode: BroadcastMode,
+    last: Option<BroadcastMode>,
+}
+
+thread_local! {
+    // Set while feeding input from code (mirrored copies, remote SendText), so it
+    // doesn't get mirrored again
+    static MIRRORING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Send `text` to the pane's process without broadcasting it to other panes.
+pub fn feed_unmirrored(terminal: &Terminal, text: &[u8]) {
+    MIRRORING.set(true);
+    terminal.feed_child(text);
+    MIRRORING.set(false);
+}
+
+/// Does a pane with `group` broadcast what's typed into it?
+pub fn broadcasts(mode: BroadcastMode, group: Option<&str>) -> bool {
+    match mode {
+        BroadcastMode::Off => false,
+        BroadcastMode::All | BroadcastMode::Tab => true,
+        BroadcastMode::Group => group.is_some(),
+    }
+}
+
+/// Should input typed into a pane (`from_tab`, `from_group`) reach another pane?
+pub fn mirrors_to(
+    mode: BroadcastMode,
+    (from_tab, from_group): (usize, Option<&str>),
+    (to_tab, to_group): (usize, Option<&str>),
+) -> bool {
+    match mode {
+        BroadcastMode::Off => false,
+        BroadcastMode::All => true,
+        BroadcastMode::Tab => from_tab == to_tab,
+        BroadcastMode::Group => from_group.is_some() && from_group == to_group,
+    }
+}
+
+pub fn broadcast_group(container: &gtk::Box) -> Option<String> {
+    unsafe {
+        container
+            .data::<String>("broadcast_group")
+            .map(|g| g.as_ref().clone())
+    }
+}
+
+/// Put the pane in a broadcast group; `None` or "" takes it out.
+pub fn set_broadcast_group(container: &gtk::Box, group: Option<&str>) {
+    unsafe {
+        match group.filter(|g| !g.is_empty()) {
+            Some(group) => container.set_data("broadcast_group", group.to_string()),
+            None => drop(container.steal_data::<String>("broadcast_group")),
+        }
+    }
+    update_indicator(container);
+}
+
+fn window_of(widget: &impl IsA<gtk::Widget>) -> Option<gtk::ApplicationWindow> {
+    widget
+        .root()
+        .and_then(|r| r.downcast::<gtk::ApplicationWindow>().ok())
+}
+
+fn state_of(window: &gtk::ApplicationWindow) -> BroadcastState {
+    unsafe {
+        window
+            .data::<BroadcastState>("broadcast")
+            .map(|s| *s.as_ref())
+            .unwrap_or_default()
+    }
+}
+
+pub fn broadcast_mode(window: &gtk::ApplicationWindow) -> BroadcastMode {
+    state_of(window).mode
+}
+
+/// Mirror the terminal's input to the other panes while broadcasting is on.
+pub fn setup_broadcast(terminal: &Terminal, container: &gtk::Box) {
+    terminal.connect_commit(|terminal, text, _| {
+        // Only what the user types or pastes, not what other code feeds in
+        if MIRRORING.get() || !terminal.has_focus() {
+            return;
+        }
+        let Some(window) = window_of(terminal) else {
+            return;
+        };
+        if broadcast_mode(&window) == BroadcastMode::Off {
+            return;
+        }
+        for target in mirror_targets(&window, terminal) {
+            feed_unmirrored(&target, text.as_bytes());
+        }
+    });
+
+    // New panes pick up the window's mode once they're in it
+    container.connect_map(update_indicator);
+}
+
+fn mirror_targets(window: &gtk::ApplicationWindow, source: &Terminal) -> Vec<Terminal> {
+    let mode = broadcast_mode(window);
+    let Some(source_container) = pane_container_of(source) else {
+        return Vec::new();
+    };
+    let tabs = tab_workspaces(window);
+    let Some(source_tab) = tabs
+        .iter()
+        .position(|w| w.containers().contains(&source_container))
+    else {
+        return Vec::new();
+    };
+    let source_group = broadcast_group(&source_container);
+
+    let mut targets = Vec::new();
+    for (tab, workspace) in tabs.iter().enumerate() {
+        for container in workspace.containers() {
+            if container == source_container {
+                continue;
+            }
+            let group = broadcast_group(&container);
+            if mirrors_to(
+                mode,
+                (source_tab, source_group.as_deref()),
+                (tab, group.as_deref()),
+            ) {
+                targets.extend(find_terminal_in_container(&container));
+            }
+        }
+    }
+    targets
+}
+
+fn tab_workspaces(window: &gtk::ApplicationWindow) -> Vec<Workspace> {
+    window_notebook(window)
+        .map(|notebook| workspaces(&notebook))
+        .unwrap_or_default()
+}
+
+/// Title bar indicator: shown on every pane whose input is being broadcast.
+pub fn create_indicator(container: &gtk::Box) -> gtk::Image {
+    let indicator = gtk::Image::from_icon_name("network-transmit-symbolic");
+    indicator.add_css_class("broadcast-indicator");
+    indicator.set_tooltip_text(Some("Input is broadcast to other panes"));
+    indicator.set_visible(false);
+    unsafe {
+        container.set_data("broadcast_indicator", indicator.clone());
+    }
+    indicator
+}
+
+fn update_indicator(container: &gtk::Box) {
+    let mode = window_of(container).map_or(BroadcastMode::Off, |w| broadcast_mode(&w));
+    let on = broadcasts(mode, broadcast_group(container).as_deref());
+    if let Some(indicator) = unsafe { container.data::<gtk::Image>("broadcast_indicator") }
+        .map(|i| unsafe { i.as_ref() })
+    {
+        indicator.set_visible(on);
+    }
+    // The title bar is the container's first child
+    if let Some(title_bar) = container.first_child() {
+        if on {
+            title_bar.add_css_class("broadcasting");
+        } else {
+            title_bar.remove_css_class("broadcasting");
+        }
+    }
+}
+
+fn set_mode(window: &gtk::ApplicationWindow, mode: BroadcastMode) {
+    let mut state = state_of(window);
+    state.mode = mode;
+    if mode != BroadcastMode::Off {
+        state.last = Some(mode);
+    }
+    unsafe {
+        window.set_data("broadcast", state);
+    }
+    println!("Broadcast input: {}", mode.id());
+
+    for workspace in tab_workspaces(window) {
+        for container in workspace.containers() {
+            update_indicator(&container);
+        }
+    }
+}
+
+/// `win.broadcast` picks the mode (off/all/tab/group), `win.toggle-broadcast`
+/// turns the last one used on and off.
+pub fn setup_broadcast_actions(window: &gtk::ApplicationWindow) {
+    let broadcast = gio::SimpleAction::new_stateful(
+        "broadcast",
+        Some(glib::VariantTy::STRING),
+        &BroadcastMode::Off.id().to_variant(),
+    );
+    let window_weak = window.downgrade();
+    broadcast.connect_change_state(move |action, value| {
+        let Some(mode) = value
+            .and_then(|v| v.get::<String>())
+            .and_then(|id| BroadcastMode::from_id(&id))
+        else {
+            return;
+        };
+        let Some(window) = window_weak.upgrade() else {
+            return;
+        };
+        action.set_state(&mode.id().to_variant());
+        set_mode(&window, mode);
+    });
+    window.add_action(&broadcast);
+
+    let toggle = gio::SimpleAction::new("toggle-broadcast", None);
+    let window_weak = window.downgrade();
+    toggle.connect_activate(move |_, _| {
+        let Some(window) = window_weak.upgrade() else {
+            return;
+        };
+        let state = state_of(&window);
+        let next = match state.mode {
+            BroadcastMode::Off => state.last.unwrap_or(BroadcastMode::Tab),
+            _ => BroadcastMode::Off,
+        };
+        broadcast.change_state(&next.id().to_variant());
+    });
+    window.add_action(&toggle);
+}
+
+// gtk::Dialog is deprecated since GTK 4.10
+#[allow(deprecated)]
+pub fn show_group_dialog(container: &gtk::Box) {
+    let dialog = gtk::Dialog::builder()
+        .title("Broadcast Group")
+        .modal(true)
+        .build();
+    if let Some(window) = window_of(container) {
+        dialog.set_transient_for(Some(&window));
+    }
+
+    let entry = gtk::Entry::new();
+    entry.set_placeholder_text(Some("No group"));
+    entry.set_text(&broadcast_group(container).unwrap_or_default());
+    entry.set_margin_start(12);
+    entry.set_margin_end(12);
+    entry.set_margin_top(12);
+    entry.set_margin_bottom(12);
+    dialog.content_area().append(&entry);
+
+    dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+    dialog.add_button("OK", gtk::ResponseType::Ok);
+    dialog.set_default_response(gtk::ResponseType::Ok);
+
+    let container = container.clone();
+    let entry_clone = entry.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response == gtk::ResponseType::Ok {
+            set_broadcast_group(&container, Some(entry_clone.text().trim()));
+        }
+        dialog.close();
+    });
+    let dialog_clone = dialog.clone();
+    entry.connect_activate(move |_| dialog_clone.response(gtk::ResponseType::Ok));
+    dialog.present();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn who_gets_the_input() {
+        let ops = (0, Some("ops"));
+        assert!(mirrors_to(BroadcastMode::All, ops, (1, None)));
+        assert!(mirrors_to(BroadcastMode::Tab, ops, (0, None)));
+        assert!(!mirrors_to(BroadcastMode::Tab, ops, (1, Some("ops"))));
+        assert!(mirrors_to(BroadcastMode::Group, ops, (1, Some("ops"))));
+        assert!(!mirrors_to(BroadcastMode::Group, ops, (0, Some("db"))));
+        assert!(!mirrors_to(BroadcastMode::Group, (0, None), (0, None)));
+        assert!(!mirrors_to(BroadcastMode::Off, ops, (0, Some("ops"))));
+
+        assert!(broadcasts(BroadcastMode::Tab, None));
+        assert!(!broadcasts(BroadcastMode::Group, None));
+        assert_eq!(BroadcastMode::from_id("group"), Some(BroadcastMode::Group));
+        assert_eq!(BroadcastMode::from_id("everything"), None);
+    }
+}
@@ -0,0 +1,231 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use gtk4 as gtk;
+use vte4::Terminal;
+use vte4::prelude::*;
+
+use crate::config;
+use crate::layout::types::ExitPolicy;
+use crate::ui::split::stop_terminal;
+use crate::ui::terminal::{pane_exit_policy, restart_process};
+use crate::ui::workspace::{Workspace, pane_container_of, pane_id_of};
+
+// A run shorter than this counts as the command failing to start
+const QUICK_EXIT: Duration = Duration::from_secs(2);
+// Quick exits in a row before auto-restart gives up and holds the pane
+const MAX_QUICK_RESTARTS: u32 = 5;
+const RESTART_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, PartialEq)]
+pub enum ExitAction {
+    Close,
+    Hold,
+    Restart,
+    // Restarting keeps failing, hold instead of looping
+    GiveUp,
+}
+
+// Kept on the terminal for the restart bookkeeping
+struct ExitWatch {
+    started: Cell<Instant>,
+    quick_exits: Cell<u32>,
+    banner: gtk::Box,
+    label: gtk::Label,
+}
+
+/// `quick_exits` counts the runs in a row (this one included) that ended right away.
+pub fn next_action(policy: ExitPolicy, quick_exits: u32) -> ExitAction {
+    match policy {
+        ExitPolicy::Close => ExitAction::Close,
+        ExitPolicy::Hold => ExitAction::Hold,
+        ExitPolicy::Restart if quick_exits >= MAX_QUICK_RESTARTS => ExitAction::GiveUp,
+        ExitPolicy::Restart => ExitAction::Restart,
+    }
+}
+
+/// Human readable form of the wait status VTE reports.
+pub fn exit_status_text(status: i32) -> String {
+    if libc::WIFEXITED(status) {
+        match libc::WEXITSTATUS(status) {
+            0 => "Process exited".to_string(),
+            code => format!("Process exited with status {code}"),
+        }
+    } else if libc::WIFSIGNALED(status) {
+        let signal = libc::WTERMSIG(status);
+        let name = unsafe { libc::strsignal(signal) };
+        if name.is_null() {
+            format!("Process killed by signal {signal}")
+        } else {
+            let name = unsafe { std::ffi::CStr::from_ptr(name) }.to_string_lossy();
+            format!("Process killed by signal {signal} ({name})")
+        }
+    } else {
+        format!("Process ended (status {status})")
+    }
+}
+
+/// The bar shown over a held pane, hidden while the process runs.
+pub fn create_exit_banner(terminal: &Terminal, container: &gtk::Box) -> gtk::Box {
+    let label = gtk::Label::new(None);
+    label.set_hexpand(true);
+    label.set_halign(gtk::Align::Start);
+    let restart_button = gtk::Button::with_label("Restart");
+    restart_button.add_css_class("suggested-action");
+    let close_button = gtk::Button::with_label("Close");
+
+    let banner = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    banner.add_css_class("exit-banner");
+    banner.append(&label);
+    banner.append(&restart_button);
+    banner.append(&close_button);
+    banner.set_visible(false);
+
+    unsafe {
+        terminal.set_data(
+            "exit_watch",
+            ExitWatch {
+                started: Cell::new(Instant::now()),
+                quick_exits: Cell::new(0),
+                banner: banner.clone(),
+                label,
+            },
+        );
+    }
+
+    let terminal_weak = terminal.downgrade();
+    restart_button.connect_clicked(move |_| {
+        if let Some(terminal) = terminal_weak.upgrade() {
+            // Only automatic restarts count towards giving up
+            if let Some(watch) = watch_of(&terminal) {
+                watch.quick_exits.set(0);
+            }
+            restart(&terminal);
+            terminal.grab_focus();
+        }
+    });
+    let terminal_weak = terminal.downgrade();
+    let container_weak = container.downgrade();
+    close_button.connect_clicked(move |_| {
+        if let (Some(terminal), Some(container)) =
+            (terminal_weak.upgrade(), container_weak.upgrade())
+        {
+            stop_terminal(&terminal, &container);
+        }
+    });
+
+    let container_weak = container.downgrade();
+    terminal.connect_child_exited(move |terminal, status| {
+        let Some(container) = container_weak.upgrade() else {
+            return;
+        };
+        // Closing the pane ends the process too; nothing left to do then
+        if !is_open(&container) {
+            return;
+        }
+        // Held panes report no pid until they restart
+        let _ = unsafe { terminal.steal_data::<i32>("child_pid") };
+        process_ended(terminal, &container, &exit_status_text(status));
+    });
+
+    banner
+}
+
+fn is_open(container: &gtk::Box) -> bool {
+    let (Some(workspace), Some(id)) = (Workspace::for_widget(container), pane_id_of(container))
+    else {
+        return false;
+    };
+    workspace.container(id).as_ref() == Some(container)
+}
+
+fn watch_of(terminal: &Terminal) -> Option<&ExitWatch> {
+    unsafe { terminal.data::<ExitWatch>("exit_watch").map(|w| w.as_ref()) }
+}
+
+/// A shell or command that can't be started ends like one that exits at once.
+pub fn spawn_failed(terminal: &Terminal, error: &str) {
+    let Some(container) = pane_container_of(terminal) else {
+        return;
+    };
+    if is_open(&container) {
+        process_ended(terminal, &container, &format!("Could not start: {error}"));
+    }
+}
+
+fn process_ended(terminal: &Terminal, container: &gtk::Box, status_text: &str) {
+    let Some(watch) = watch_of(terminal) else {
+        return;
+    };
+    println!("Pane exited: {status_text}");
+
+    if watch.started.get().elapsed() < QUICK_EXIT {
+        watch.quick_exits.set(watch.quick_exits.get() + 1);
+    } else {
+        watch.quick_exits.set(0);
+    }
+    let policy = pane_exit_policy(terminal).unwrap_or(config::get().terminal.on_exit);
+
+    match next_action(policy, watch.quick_exits.get()) {
+        ExitAction::Close => stop_terminal(terminal, container),
+        ExitAction::Hold => show_banner(watch, status_text),
+        ExitAction::GiveUp => show_banner(
+            watch,
+            &format!("{status_text}, not restarting after {MAX_QUICK_RESTARTS} quick exits"),
+        ),
+        ExitAction::Restart => {
+            // A short pause so a failing command doesn't spin
+            let terminal_weak = terminal.downgrade();
+            let container_weak = container.downgrade();
+            glib::timeout_add_local_once(RESTART_DELAY, move || {
+                if let (Some(terminal), Some(container)) =
+                    (terminal_weak.upgrade(), container_weak.upgrade())
+                {
+                    if is_open(&container) {
+                        restart(&terminal);
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn show_banner(watch: &ExitWatch, text: &str) {
+    watch.label.set_text(text);
+    watch.banner.set_visible(true);
+}
+
+/// Start the pane's process again and hide the exit banner.
+fn restart(terminal: &Terminal) {
+    if let Some(watch) = watch_of(terminal) {
+        watch.banner.set_visible(false);
+        watch.started.set(Instant::now());
+    }
+    // Keep the old output, but start the new run on a fresh line
+    terminal.feed(b"\r\n");
+    restart_process(terminal);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies() {
+        assert_eq!(next_action(ExitPolicy::Close, 0), ExitAction::Close);
+        assert_eq!(next_action(ExitPolicy::Hold, 3), ExitAction::Hold);
+        assert_eq!(next_action(ExitPolicy::Restart, 1), ExitAction::Restart);
+        assert_eq!(
+            next_action(ExitPolicy::Restart, MAX_QUICK_RESTARTS),
+            ExitAction::GiveUp
+        );
+    }
+
+    #[test]
+    fn exit_statuses() {
+        assert_eq!(exit_status_text(0), "Process exited");
+        // Exit codes sit in the second byte of the wait status
+        assert_eq!(exit_status_text(2 << 8), "Process exited with status 2");
+        assert!(exit_status_text(libc::SIGKILL).starts_with("Process killed by signal 9"));
+    }
+}
@@ -0,0 +1,228 @@
+use std::path::{Path, PathBuf};
+
+use gtk::gio;
+use gtk4 as gtk;
+use vte4::Terminal;
+use vte4::prelude::*;
+
+use crate::config;
+use crate::layout::extract::file_uri_to_path;
+use crate::ui::search::{PCRE2_MULTILINE, PCRE2_UCP, PCRE2_UTF};
+
+// Trailing punctuation is usually the sentence, not the link
+const URL_REGEX: &str = r#"\b(?:https?|ftp|file)://[^\s<>"'`]*[^\s<>"'`.,;:!?)\]]"#;
+
+// src/main.rs:12, ./foo.c:3:7, /etc/hosts:1 as printed by compilers and grep -n
+const FILE_REGEX: &str = r"(?:~|\.{1,2})?/?(?:[\w.+-]+/)*[\w+-][\w.+-]*\.\w+:\d+(?::\d+)?\b";
+
+#[derive(Debug, PartialEq)]
+pub enum Link {
+    Url(String),
+    File {
+        path: String,
+        line: u32,
+        column: Option<u32>,
+    },
+}
+
+/// Turn on OSC 8 hyperlinks and register the URL and file:line patterns.
+pub fn setup_links(terminal: &Terminal) {
+    terminal.set_allow_hyperlink(true);
+
+    // URLs first: VTE reports the first pattern that matches
+    for pattern in [URL_REGEX, FILE_REGEX] {
+        match vte4::Regex::for_match(pattern, PCRE2_UTF | PCRE2_UCP | PCRE2_MULTILINE) {
+            Ok(regex) => {
+                let tag = terminal.match_add_regex(&regex, 0);
+                terminal.match_set_cursor_name(tag, "pointer");
+            }
+            Err(e) => eprintln!("Invalid link pattern {pattern}: {e}"),
+        }
+    }
+
+    // Ctrl+click opens, a plain click keeps selecting text
+    let gesture = gtk::GestureClick::new();
+    gesture.set_button(1);
+    // VTE handles clicks itself, so look at them before it does
+    gesture.set_propagation_phase(gtk::PropagationPhase::Capture);
+    gesture.connect_released(|gesture, n_press, x, y| {
+        let ctrl = gesture
+            .current_event_state()
+            .contains(gtk::gdk::ModifierType::CONTROL_MASK);
+        if n_press != 1 || !ctrl {
+            return;
+        }
+        let Some(terminal) = gesture.widget().and_then(|w| w.downcast::<Terminal>().ok()) else {
+            return;
+        };
+        if let Some(link) = link_at(&terminal, x, y) {
+            open_link(&terminal, &link);
+        }
+    });
+    terminal.add_controller(gesture);
+}
+
+/// Text of the OSC 8 hyperlink or detected link under the pointer.
+pub fn link_at(terminal: &Terminal, x: f64, y: f64) -> Option<String> {
+    terminal
+        .check_hyperlink_at(x, y)
+        .or_else(|| terminal.check_match_at(x, y).0)
+        .map(|link| link.to_string())
+}
+
+pub fn open_link(terminal: &Terminal, text: &str) {
+    match parse_link(text) {
+        Some(Link::Url(url)) => launch_uri(terminal, &url),
+        Some(Link::File { path, line, column }) => {
+            let cwd = terminal
+                .current_directory_uri()
+                .and_then(|u| file_uri_to_path(&u));
+            let path = resolve_path(&path, cwd.as_deref(), &glib::home_dir());
+            if !path.exists() {
+                eprintln!("No such file: {}", path.display());
+                return;
+            }
+            open_file(terminal, &path, line, column);
+        }
+        None => eprintln!("Not a link: {text}"),
+    }
+}
+
+fn open_file(terminal: &Terminal, path: &Path, line: u32, column: Option<u32>) {
+    let Some(editor) = config::get().terminal.editor.clone() else {
+        // Without an editor command the line number is lost
+        launch_uri(terminal, &gio::File::for_path(path).uri());
+        return;
+    };
+    let argv = editor_argv(&editor, &path.to_string_lossy(), line, column.unwrap_or(1));
+    let argv: Vec<&std::ffi::OsStr> = argv.iter().map(|a| a.as_ref()).collect();
+    if let Err(e) = gio::Subprocess::newv(&argv, gio::SubprocessFlags::NONE) {
+        eprintln!("Could not start editor '{editor}': {e}");
+    }
+}
+
+fn launch_uri(terminal: &Terminal, uri: &str) {
+    let window = terminal
+        .root()
+        .and_then(|r| r.downcast::<gtk::Window>().ok());
+    let uri_owned = uri.to_string();
+    gtk::UriLauncher::new(uri).launch(window.as_ref(), gio::Cancellable::NONE, move |res| {
+        if let Err(e) = res {
+            eprintln!("Could not open {uri_owned}: {e}");
+        }
+    });
+}
+
+pub fn parse_link(text: &str) -> Option<Link> {
+    if text.contains("://") {
+        return Some(Link::Url(text.to_string()));
+    }
+
+    // path:line or path:line:column
+    let mut parts = text.rsplitn(3, ':');
+    let last = parts.next()?.parse::<u32>().ok()?;
+    let middle = parts.next()?;
+    match (middle.parse::<u32>(), parts.next()) {
+        (Ok(line), Some(path)) if !path.is_empty() => Some(Link::File {
+            path: path.to_string(),
+            line,
+            column: Some(last),
+        }),
+        _ if !middle.is_empty() => {
+            let path = match parts.next() {
+                Some(rest) => format!("{rest}:{middle}"),
+                None => middle.to_string(),
+            };
+            Some(Link::File {
+                path,
+                line: last,
+                column: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Relative paths are relative to the pane's current directory.
+pub fn resolve_path(path: &str, cwd: Option<&str>, home: &Path) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        return home.join(rest);
+    }
+    let path = Path::new(path);
+    match cwd {
+        Some(cwd) if path.is_relative() => Path::new(cwd).join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// The editor command with {file}, {line} and {column} filled in.
+pub fn editor_argv(template: &str, file: &str, line: u32, column: u32) -> Vec<String> {
+    let argv: Vec<String> = template
+        .split_whitespace()
+        .map(|arg| {
+            arg.replace("{file}", file)
+                .replace("{line}", &line.to_string())
+                .replace("{column}", &column.to_string())
+        })
+        .collect();
+    // No placeholder at all: just pass the file
+    if template.contains("{file}") {
+        argv
+    } else {
+        argv.into_iter().chain([file.to_string()]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, line: u32, column: Option<u32>) -> Option<Link> {
+        Some(Link::File {
+            path: path.into(),
+            line,
+            column,
+        })
+    }
+
+    #[test]
+    fn links() {
+        assert_eq!(
+            parse_link("https://example.com/a?b=1"),
+            Some(Link::Url("https://example.com/a?b=1".into()))
+        );
+        assert_eq!(parse_link("src/main.rs:12"), file("src/main.rs", 12, None));
+        assert_eq!(parse_link("./foo.c:3:7"), file("./foo.c", 3, Some(7)));
+        assert_eq!(parse_link("main.rs"), None);
+        assert_eq!(parse_link(":12"), None);
+    }
+
+    #[test]
+    fn paths_resolve_against_the_pane() {
+        let home = Path::new("/home/dana");
+        assert_eq!(
+            resolve_path("src/lib.rs", Some("/work/api"), home),
+            PathBuf::from("/work/api/src/lib.rs")
+        );
+        assert_eq!(
+            resolve_path("/etc/hosts", Some("/work"), home),
+            PathBuf::from("/etc/hosts")
+        );
+        assert_eq!(
+            resolve_path("~/notes.md", Some("/work"), home),
+            PathBuf::from("/home/dana/notes.md")
+        );
+    }
+
+    #[test]
+    fn editor_commands() {
+        assert_eq!(
+            editor_argv("code --goto {file}:{line}:{column}", "/a.rs", 4, 2),
+            vec!["code", "--goto", "/a.rs:4:2"]
+        );
+        assert_eq!(
+            editor_argv("gvim +{line}", "/a.rs", 4, 1),
+            vec!["gvim", "+4", "/a.rs"]
+        );
+    }
+}
@@ -8,33 +8,66 @@ pub fn setup_global_menu(window: &gtk::ApplicationWindow) {
     let menubar = gio::Menu::new();
     let file_menu = gio::Menu::new();
 
-    file_menu.append(Some("Save Layout"), Some("app.save-layout"));
-    file_menu.append(Some("Load Layout"), Some("app.load-layout"));
+    file_menu.append(Some("New Window"), Some("app.new-window"));
+    file_menu.append(Some("Save Layout"), Some("win.save-layout"));
+    file_menu.append(Some("Load Layout"), Some("win.load-layout"));
     menubar.append_submenu(Some("File"), &file_menu);
 
-    let app = window.application().unwrap();
+    let tab_menu = gio::Menu::new();
+    tab_menu.append(Some("New Tab"), Some("win.new-tab"));
+    tab_menu.append(Some("Rename Tab"), Some("win.rename-tab"));
+    tab_menu.append(Some("Move Tab Left"), Some("win.move-tab-left"));
+    tab_menu.append(Some("Move Tab Right"), Some("win.move-tab-right"));
+    tab_menu.append(Some("Close Tab"), Some("win.close-tab"));
+    menubar.append_submenu(Some("Tabs"), &tab_menu);
 
-    // Save: לוקח תמיד את ה-child הנוכחי של החלון
+    let pane_menu = gio::Menu::new();
+    pane_menu.append(Some("Split Horizontal"), Some("win.split-horizontal"));
+    pane_menu.append(Some("Split Vertical"), Some("win.split-vertical"));
+    pane_menu.append(Some("Next Pane"), Some("win.focus-next"));
+    pane_menu.append(Some("Previous Pane"), Some("win.focus-previous"));
+    pane_menu.append(Some("Find…"), Some("win.find"));
+    pane_menu.append(Some("Clear Scrollback"), Some("win.clear-scrollback"));
+    pane_menu.append(Some("Zoom In"), Some("win.zoom-in"));
+    pane_menu.append(Some("Zoom Out"), Some("win.zoom-out"));
+    pane_menu.append(Some("Normal Size"), Some("win.zoom-reset"));
+    let broadcast_menu = gio::Menu::new();
+    for (label, mode) in [
+        ("Off", "off"),
+        ("All Panes in Window", "all"),
+        ("Current Tab", "tab"),
+        ("Same Group", "group"),
+    ] {
+        let item = gio::MenuItem::new(Some(label), None);
+        item.set_action_and_target_value(Some("win.broadcast"), Some(&mode.to_variant()));
+        broadcast_menu.append_item(&item);
+    }
+    pane_menu.append_submenu(Some("Broadcast Input"), &broadcast_menu);
+    pane_menu.append(Some("Close Pane"), Some("win.close-pane"));
+    menubar.append_submenu(Some("Panes"), &pane_menu);
+
+    // Save: כל הטאבים של החלון הזה
     let save_action = gio::SimpleAction::new("save-layout", None);
-    let win_for_save = window.clone();
+    let win_for_save = window.downgrade();
     save_action.connect_activate(move |_, _| {
-        if let Some(child) = win_for_save.child() {
-            save_layout(&child);
+        if let Some(win) = win_for_save.upgrade() {
+            save_layout(&win);
         }
     });
-    app.add_action(&save_action);
+    window.add_action(&save_action);
 
     // Load
     let load_action = gio::SimpleAction::new("load-layout", None);
-    let win_for_load = window.clone();
+    let win_for_load = window.downgrade();
     load_action.connect_activate(move |_, _| {
-        load_layout(&win_for_load);
+        if let Some(win) = win_for_load.upgrade() {
+            load_layout(&win);
+        }
     });
-    app.add_action(&load_action);
-
-    // קיצורים
-    app.set_accels_for_action("app.save-layout", &["<Primary>S"]);
-    app.set_accels_for_action("app.load-layout", &["<Primary>O"]);
+    window.add_action(&load_action);
 
-    app.set_menubar(Some(&menubar));
+    // קיצורים: config/keybindings.rs
+    if let Some(app) = window.application() {
+        app.set_menubar(Some(&menubar));
+    }
 }
@@ -1,3 +1,11 @@
+pub mod broadcast;
+pub mod exit;
+pub mod links;
 pub mod menus;
+pub mod scrollback;
+pub mod search;
 pub mod split;
+pub mod tabs;
 pub mod terminal;
+pub mod theme;
+pub mod workspace;
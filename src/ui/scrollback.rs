@@ -0,0 +1,259 @@
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use gtk::gio;
+use gtk4 as gtk;
+use vte4::prelude::*;
+use vte4::{Format, Terminal, WriteFlags};
+
+use crate::util::errors::show_error_dialog;
+
+// Kept on the terminal while it is being logged
+struct PaneLog {
+    path: PathBuf,
+    file: RefCell<File>,
+    // First row that hasn't been written yet
+    next_row: Cell<libc::c_long>,
+    handler: RefCell<Option<glib::SignalHandlerId>>,
+}
+
+/// Write the whole scrollback to `path`: HTML (with colours) for .html files, plain text otherwise.
+pub fn export_scrollback(terminal: &Terminal, path: &Path) -> Result<(), String> {
+    let is_html = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("html") || e.eq_ignore_ascii_case("htm"));
+
+    if is_html {
+        // The vertical adjustment spans the scrollback plus the screen, in rows
+        let rows = terminal.vadjustment();
+        let (first, last) = rows
+            .map(|a| (a.lower() as libc::c_long, a.upper() as libc::c_long))
+            .unwrap_or((0, terminal.row_count()));
+        let body = terminal
+            .text_range_format(Format::Html, first, 0, last, 0)
+            .0
+            .unwrap_or_default();
+        let title = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        return std::fs::write(path, html_document(&title, &body)).map_err(|e| e.to_string());
+    }
+
+    let file = gio::File::for_path(path);
+    let stream = file
+        .replace(
+            None,
+            false,
+            gio::FileCreateFlags::NONE,
+            gio::Cancellable::NONE,
+        )
+        .map_err(|e| e.to_string())?;
+    terminal
+        .write_contents_sync(&stream, WriteFlags::Default, gio::Cancellable::NONE)
+        .map_err(|e| e.to_string())?;
+    stream
+        .close(gio::Cancellable::NONE)
+        .map_err(|e| e.to_string())
+}
+
+// FileChooserDialog is deprecated since GTK 4.10, same as the layout dialogs
+#[allow(deprecated)]
+pub fn save_scrollback_dialog(terminal: &Terminal) {
+    let dialog = gtk::FileChooserDialog::builder()
+        .title("Save Scrollback")
+        .action(gtk::FileChooserAction::Save)
+        .modal(true)
+        .build();
+    dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+    dialog.add_button("Save", gtk::ResponseType::Accept);
+    dialog.set_current_name("scrollback.txt");
+
+    let text = gtk::FileFilter::new();
+    text.set_name(Some("Plain text"));
+    text.add_suffix("txt");
+    text.add_suffix("log");
+    let html = gtk::FileFilter::new();
+    html.set_name(Some("HTML (with colours)"));
+    html.add_suffix("html");
+    html.add_suffix("htm");
+    dialog.add_filter(&text);
+    dialog.add_filter(&html);
+
+    if let Some(window) = terminal
+        .root()
+        .and_then(|r| r.downcast::<gtk::Window>().ok())
+    {
+        dialog.set_transient_for(Some(&window));
+    }
+
+    let terminal = terminal.clone();
+    dialog.connect_response(move |d, resp| {
+        if resp == gtk::ResponseType::Accept {
+            if let Some(path) = d.file().and_then(|f| f.path()) {
+                if let Err(e) = export_scrollback(&terminal, &path) {
+                    show_error_dialog("Failed to save scrollback", &e);
+                }
+            }
+        }
+        d.close();
+    });
+    dialog.present();
+}
+
+#[allow(deprecated)]
+pub fn start_logging_dialog(terminal: &Terminal) {
+    let dialog = gtk::FileChooserDialog::builder()
+        .title("Log Output To")
+        .action(gtk::FileChooserAction::Save)
+        .modal(true)
+        .build();
+    dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+    dialog.add_button("Start Logging", gtk::ResponseType::Accept);
+    dialog.set_current_name("terminal.log");
+
+    if let Some(window) = terminal
+        .root()
+        .and_then(|r| r.downcast::<gtk::Window>().ok())
+    {
+        dialog.set_transient_for(Some(&window));
+    }
+
+    let terminal = terminal.clone();
+    dialog.connect_response(move |d, resp| {
+        if resp == gtk::ResponseType::Accept {
+            if let Some(path) = d.file().and_then(|f| f.path()) {
+                if let Err(e) = start_logging(&terminal, &path) {
+                    show_error_dialog("Failed to start logging", &e);
+                }
+            }
+        }
+        d.close();
+    });
+    dialog.present();
+}
+
+/// Append every line the pane prints from now on to `path`, each with a timestamp.
+pub fn start_logging(terminal: &Terminal, path: &Path) -> Result<(), String> {
+    stop_logging(terminal);
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+    let file = File::options()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+
+    let log = PaneLog {
+        path: path.to_path_buf(),
+        file: RefCell::new(file),
+        next_row: Cell::new(terminal.cursor_position().1),
+        handler: RefCell::new(None),
+    };
+    unsafe {
+        terminal.set_data("log", log);
+    }
+
+    // Only finished lines are written; the cursor's line may still change
+    let handler = terminal.connect_contents_changed(|terminal| {
+        let Some(log) = (unsafe { terminal.data::<PaneLog>("log").map(|l| l.as_ref()) }) else {
+            return;
+        };
+        let cursor_row = terminal.cursor_position().1;
+        let next_row = log.next_row.get();
+        if cursor_row < next_row {
+            // The terminal was reset or cleared
+            log.next_row.set(cursor_row);
+            return;
+        }
+        if cursor_row == next_row {
+            return;
+        }
+        let text = terminal
+            .text_range_format(Format::Text, next_row, 0, cursor_row, 0)
+            .0
+            .unwrap_or_default();
+        log.next_row.set(cursor_row);
+
+        let lines = timestamp_lines(&text, &now());
+        if let Err(e) = log.file.borrow_mut().write_all(lines.as_bytes()) {
+            eprintln!("Logging to {} failed: {e}", log.path.display());
+        }
+    });
+    unsafe {
+        if let Some(log) = terminal.data::<PaneLog>("log") {
+            log.as_ref().handler.replace(Some(handler));
+        }
+    }
+    Ok(())
+}
+
+pub fn stop_logging(terminal: &Terminal) {
+    let log = unsafe { terminal.steal_data::<PaneLog>("log") };
+    if let Some(log) = log {
+        if let Some(handler) = log.handler.take() {
+            terminal.disconnect(handler);
+        }
+        let _ = log.file.borrow_mut().flush();
+    }
+}
+
+/// File the pane is being logged to, if any.
+pub fn log_path(terminal: &Terminal) -> Option<PathBuf> {
+    unsafe {
+        terminal
+            .data::<PaneLog>("log")
+            .map(|log| log.as_ref().path.clone())
+    }
+}
+
+fn now() -> String {
+    glib::DateTime::now_local()
+        .and_then(|t| t.format("%Y-%m-%d %H:%M:%S"))
+        .map(|s| s.to_string())
+        .unwrap_or_default()
+}
+
+/// Prefix every line with `[timestamp] `; trailing spaces VTE pads rows with are dropped.
+pub fn timestamp_lines(text: &str, timestamp: &str) -> String {
+    text.lines()
+        .map(|line| format!("[{timestamp}] {}\n", line.trim_end()))
+        .collect()
+}
+
+pub fn html_document(title: &str, body: &str) -> String {
+    let title = title
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n{body}\n</body>\n</html>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_lines_get_timestamps() {
+        let text = "$ make   \nok\n";
+        assert_eq!(
+            timestamp_lines(text, "2024-05-01 10:00:00"),
+            "[2024-05-01 10:00:00] $ make\n[2024-05-01 10:00:00] ok\n"
+        );
+        assert_eq!(timestamp_lines("", "t"), "");
+    }
+
+    #[test]
+    fn html_export_is_a_full_document() {
+        let html = html_document("a <b>", "<pre>hi</pre>");
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>a &lt;b&gt;</title>"));
+        assert!(html.contains("<pre>hi</pre>"));
+    }
+}
@@ -0,0 +1,237 @@
+use gtk4 as gtk;
+use vte4::Terminal;
+use vte4::prelude::*;
+
+// PCRE2 compile flags (pcre2.h); VTE wants UTF and MULTILINE for its regexes
+const PCRE2_CASELESS: u32 = 0x0000_0008;
+pub(crate) const PCRE2_MULTILINE: u32 = 0x0000_0400;
+pub(crate) const PCRE2_UCP: u32 = 0x0002_0000;
+pub(crate) const PCRE2_UTF: u32 = 0x0008_0000;
+
+/// Search bar shown between a pane's title bar and its terminal.
+pub fn create_search_bar(terminal: &Terminal) -> gtk::SearchBar {
+    let entry = gtk::SearchEntry::new();
+    entry.set_hexpand(true);
+    entry.set_placeholder_text(Some("Search scrollback"));
+
+    let case_button = gtk::ToggleButton::with_label("Aa");
+    case_button.set_tooltip_text(Some("Match Case"));
+    let regex_button = gtk::ToggleButton::with_label(".*");
+    regex_button.set_tooltip_text(Some("Regular Expression"));
+    let wrap_button = gtk::ToggleButton::new();
+    wrap_button.set_icon_name("media-playlist-repeat-symbolic");
+    wrap_button.set_tooltip_text(Some("Wrap Around"));
+    wrap_button.set_active(true);
+    let previous_button = gtk::Button::from_icon_name("go-up-symbolic");
+    previous_button.set_tooltip_text(Some("Previous Match"));
+    let next_button = gtk::Button::from_icon_name("go-down-symbolic");
+    next_button.set_tooltip_text(Some("Next Match"));
+
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    row.append(&entry);
+    for button in [&case_button, &regex_button, &wrap_button] {
+        button.add_css_class("flat");
+        row.append(button);
+    }
+    for button in [&previous_button, &next_button] {
+        button.add_css_class("flat");
+        row.append(button);
+    }
+
+    let bar = gtk::SearchBar::new();
+    bar.set_child(Some(&row));
+    bar.connect_entry(&entry);
+    bar.set_show_close_button(true);
+
+    // Re-compile whenever the text or an option changes
+    let update = {
+        let terminal = terminal.clone();
+        let entry = entry.clone();
+        let case_button = case_button.clone();
+        let regex_button = regex_button.clone();
+        move || {
+            set_search(
+                &terminal,
+                &entry,
+                case_button.is_active(),
+                regex_button.is_active(),
+            )
+        }
+    };
+    let update_clone = update.clone();
+    entry.connect_search_changed(move |_| update_clone());
+    for button in [&case_button, &regex_button] {
+        let update = update.clone();
+        button.connect_toggled(move |_| update());
+    }
+    let terminal_clone = terminal.clone();
+    wrap_button.connect_toggled(move |b| terminal_clone.search_set_wrap_around(b.is_active()));
+    terminal.search_set_wrap_around(true);
+
+    // Enter / Ctrl+G go forward, Shift+Enter / Ctrl+Shift+G back
+    let find = |forward: bool| {
+        let terminal = terminal.clone();
+        let entry = entry.clone();
+        move || find_match(&terminal, &entry, forward)
+    };
+    let next = find(true);
+    entry.connect_activate(move |_| next());
+    let next = find(true);
+    entry.connect_next_match(move |_| next());
+    let previous = find(false);
+    entry.connect_previous_match(move |_| previous());
+    let next = find(true);
+    next_button.connect_clicked(move |_| next());
+    let previous = find(false);
+    previous_button.connect_clicked(move |_| previous());
+
+    let shift_enter = gtk::EventControllerKey::new();
+    let previous = find(false);
+    shift_enter.connect_key_pressed(move |_, key, _, state| {
+        let is_enter = matches!(key, gtk::gdk::Key::Return | gtk::gdk::Key::KP_Enter);
+        if is_enter && state.contains(gtk::gdk::ModifierType::SHIFT_MASK) {
+            previous();
+            return glib::Propagation::Stop;
+        }
+        glib::Propagation::Proceed
+    });
+    entry.add_controller(shift_enter);
+
+    // Closing hands the keyboard back to the terminal and drops the highlight
+    let terminal_clone = terminal.clone();
+    bar.connect_search_mode_enabled_notify(move |bar| {
+        if !bar.is_search_mode() {
+            terminal_clone.search_set_regex(None, 0);
+            terminal_clone.unselect_all();
+            terminal_clone.grab_focus();
+        }
+    });
+
+    bar
+}
+
+/// Show or hide a pane's search bar.
+pub fn toggle_search(container: &gtk::Box) {
+    let Some(bar) = search_bar_of(container) else {
+        return;
+    };
+    if bar.is_search_mode() {
+        bar.set_search_mode(false);
+        return;
+    }
+    bar.set_search_mode(true);
+    if let Some(entry) = search_entry_of(&bar) {
+        entry.grab_focus();
+        entry.select_region(0, -1);
+    }
+}
+
+fn search_bar_of(container: &gtk::Box) -> Option<gtk::SearchBar> {
+    let mut child = container.first_child();
+    while let Some(widget) = child {
+        if let Ok(bar) = widget.clone().downcast::<gtk::SearchBar>() {
+            return Some(bar);
+        }
+        child = widget.next_sibling();
+    }
+    None
+}
+
+// search bar > row > entry
+fn search_entry_of(bar: &gtk::SearchBar) -> Option<gtk::SearchEntry> {
+    bar.child()?
+        .first_child()?
+        .downcast::<gtk::SearchEntry>()
+        .ok()
+}
+
+fn set_search(terminal: &Terminal, entry: &gtk::SearchEntry, case_sensitive: bool, regex: bool) {
+    entry.remove_css_class("error");
+    let text = entry.text();
+    if text.is_empty() {
+        terminal.search_set_regex(None, 0);
+        terminal.unselect_all();
+        return;
+    }
+
+    let pattern = search_pattern(&text, regex);
+    match vte4::Regex::for_search(&pattern, search_flags(case_sensitive)) {
+        Ok(regex) => {
+            terminal.search_set_regex(Some(&regex), 0);
+            // Search as you type, starting from the bottom like most terminals
+            terminal.unselect_all();
+            if !terminal.search_find_previous() {
+                entry.add_css_class("error");
+            }
+        }
+        Err(e) => {
+            // Half-typed regexes are expected, so only mark the entry
+            terminal.search_set_regex(None, 0);
+            entry.add_css_class("error");
+            entry.set_tooltip_text(Some(e.message()));
+            return;
+        }
+    }
+    entry.set_tooltip_text(None);
+}
+
+fn find_match(terminal: &Terminal, entry: &gtk::SearchEntry, forward: bool) {
+    if terminal.search_get_regex().is_none() {
+        return;
+    }
+    let found = if forward {
+        terminal.search_find_next()
+    } else {
+        terminal.search_find_previous()
+    };
+    if found {
+        entry.remove_css_class("error");
+    } else {
+        entry.add_css_class("error");
+    }
+}
+
+/// The regex to search for; plain text is escaped so it matches literally.
+pub fn search_pattern(text: &str, regex: bool) -> String {
+    if regex {
+        return text.to_string();
+    }
+    let mut pattern = String::with_capacity(text.len());
+    for c in text.chars() {
+        // A backslash before any ASCII punctuation is a literal in PCRE2
+        if c.is_ascii_punctuation() {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern
+}
+
+pub fn search_flags(case_sensitive: bool) -> u32 {
+    let flags = PCRE2_UTF | PCRE2_UCP | PCRE2_MULTILINE;
+    if case_sensitive {
+        flags
+    } else {
+        flags | PCRE2_CASELESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_is_escaped() {
+        assert_eq!(search_pattern("a.b*(c)", false), r"a\.b\*\(c\)");
+        assert_eq!(search_pattern(r"C:\Temp [x]", false), r"C\:\\Temp \[x\]");
+        assert_eq!(search_pattern("שלום", false), "שלום");
+        assert_eq!(search_pattern("a.b*", true), "a.b*");
+    }
+
+    #[test]
+    fn case_flag() {
+        assert_eq!(search_flags(false) & PCRE2_CASELESS, PCRE2_CASELESS);
+        assert_eq!(search_flags(true) & PCRE2_CASELESS, 0);
+        assert_ne!(search_flags(true) & PCRE2_MULTILINE, 0);
+    }
+}
@@ -0,0 +1,253 @@
+use gtk::gio;
+use gtk::prelude::*;
+use gtk4 as gtk;
+
+use crate::layout::tree::LayoutTree;
+use crate::layout::types::WindowLayout;
+use crate::ui::split::next_pane_layout;
+use crate::ui::terminal::show_rename_dialog;
+use crate::ui::workspace::Workspace;
+
+/// The window child: one notebook page per workspace.
+pub fn create_notebook() -> gtk::Notebook {
+    let notebook = gtk::Notebook::new();
+    notebook.set_scrollable(true);
+    notebook.set_show_border(false);
+    notebook.set_hexpand(true);
+    notebook.set_vexpand(true);
+
+    // A single tab doesn't need a tab bar
+    notebook.set_show_tabs(false);
+    notebook.connect_page_added(|nb, _, _| nb.set_show_tabs(nb.n_pages() > 1));
+    notebook.connect_page_removed(|nb, _, _| nb.set_show_tabs(nb.n_pages() > 1));
+
+    // The terminal that had focus is now hidden; hand focus to the new tab
+    notebook.connect_switch_page(|_, page, _| {
+        if let Some(workspace) = Workspace::for_widget(page) {
+            glib::idle_add_local_once(move || workspace.focus_last());
+        }
+    });
+
+    notebook
+}
+
+pub fn single_tab(workspace: &Workspace) -> gtk::Notebook {
+    let notebook = create_notebook();
+    add_tab(&notebook, workspace, None);
+    notebook
+}
+
+/// Notebook holding every tab of a saved window.
+pub fn build_notebook(layout: &WindowLayout) -> Result<gtk::Notebook, String> {
+    let notebook = create_notebook();
+    set_tabs(&notebook, layout)?;
+    Ok(notebook)
+}
+
+pub fn window_notebook(window: &gtk::ApplicationWindow) -> Option<gtk::Notebook> {
+    window
+        .child()
+        .and_then(|c| c.downcast::<gtk::Notebook>().ok())
+}
+
+pub fn workspaces(notebook: &gtk::Notebook) -> Vec<Workspace> {
+    (0..notebook.n_pages())
+        .filter_map(|i| notebook.nth_page(Some(i)))
+        .filter_map(|page| Workspace::for_widget(&page))
+        .collect()
+}
+
+pub fn current_workspace(window: &gtk::ApplicationWindow) -> Option<Workspace> {
+    let notebook = window_notebook(window)?;
+    let page = notebook.nth_page(notebook.current_page())?;
+    Workspace::for_widget(&page)
+}
+
+/// Append a tab for the workspace. Without a title it becomes "Tab N".
+pub fn add_tab(notebook: &gtk::Notebook, workspace: &Workspace, title: Option<&str>) -> u32 {
+    let title = title
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Tab {}", notebook.n_pages() + 1));
+
+    let label = gtk::Label::new(Some(&title));
+    let close_button = gtk::Button::from_icon_name("window-close-symbolic");
+    close_button.add_css_class("flat");
+    close_button.set_tooltip_text(Some("Close Tab"));
+
+    let tab = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    tab.append(&label);
+    tab.append(&close_button);
+
+    // Double click renames, like the pane titles
+    let gesture = gtk::GestureClick::new();
+    gesture.set_button(1);
+    let label_clone = label.clone();
+    let tab_clone = tab.clone();
+    gesture.connect_pressed(move |_, n_press, _, _| {
+        if n_press == 2 {
+            show_rename_dialog("Rename Tab", &label_clone, &tab_clone);
+        }
+    });
+    label.add_controller(gesture);
+
+    let page = workspace.widget().clone();
+    close_button.connect_clicked(move |_| close_tab(&page));
+
+    let index = notebook.append_page(workspace.widget(), Some(&tab));
+    notebook.set_tab_reorderable(workspace.widget(), true);
+    index
+}
+
+pub fn tab_title(notebook: &gtk::Notebook, page: &impl IsA<gtk::Widget>) -> String {
+    tab_label(notebook, page)
+        .map(|l| l.text().to_string())
+        .unwrap_or_default()
+}
+
+fn tab_label(notebook: &gtk::Notebook, page: &impl IsA<gtk::Widget>) -> Option<gtk::Label> {
+    notebook
+        .tab_label(page)?
+        .first_child()?
+        .downcast::<gtk::Label>()
+        .ok()
+}
+
+fn notebook_of(page: &impl IsA<gtk::Widget>) -> Option<gtk::Notebook> {
+    page.ancestor(gtk::Notebook::static_type())
+        .and_then(|w| w.downcast::<gtk::Notebook>().ok())
+}
+
+/// Remove a tab and its panes. Closing the last tab closes the window.
+pub fn close_tab(page: &impl IsA<gtk::Widget>) {
+    let Some(notebook) = notebook_of(page) else {
+        return;
+    };
+    let window = notebook
+        .root()
+        .and_then(|r| r.downcast::<gtk::Window>().ok());
+
+    if let Some(workspace) = Workspace::for_widget(page) {
+        workspace.release();
+    }
+    notebook.remove_page(notebook.page_num(page));
+
+    if notebook.n_pages() == 0 {
+        if let Some(window) = window {
+            window.close();
+        }
+    }
+}
+
+/// Replace all tabs with the ones from a saved window.
+pub fn set_tabs(notebook: &gtk::Notebook, layout: &WindowLayout) -> Result<(), String> {
+    // Build everything first so a broken file leaves the current tabs alone
+    if layout.tabs.is_empty() {
+        return Err("Layout has no tabs".into());
+    }
+    let trees = layout
+        .tabs
+        .iter()
+        .map(|tab| LayoutTree::from_layout(&tab.root))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Old panes go first, so reloading a layout keeps its pane ids
+    while let Some(page) = notebook.nth_page(Some(0)) {
+        if let Some(workspace) = Workspace::for_widget(&page) {
+            workspace.release();
+        }
+        notebook.remove_page(Some(0));
+    }
+    let workspaces: Vec<_> = layout
+        .tabs
+        .iter()
+        .zip(trees)
+        .map(|(tab, tree)| {
+            let workspace = Workspace::new(tree);
+            workspace.set_focused_pane(tab.focused.clone());
+            (tab.title.as_str(), workspace)
+        })
+        .collect();
+    for (title, workspace) in &workspaces {
+        add_tab(notebook, workspace, Some(title));
+    }
+    notebook.set_current_page(Some(layout.active_tab.min(workspaces.len() - 1) as u32));
+    Ok(())
+}
+
+pub fn setup_tab_actions(window: &gtk::ApplicationWindow) {
+    let new_tab = gio::SimpleAction::new("new-tab", None);
+    let win = window.downgrade();
+    new_tab.connect_activate(move |_, _| {
+        let Some(notebook) = win.upgrade().and_then(|w| window_notebook(&w)) else {
+            return;
+        };
+        let (tree, _) = LayoutTree::with_pane(next_pane_layout());
+        let workspace = Workspace::new(tree);
+        let index = add_tab(&notebook, &workspace, None);
+        notebook.set_current_page(Some(index));
+    });
+    window.add_action(&new_tab);
+
+    let close = gio::SimpleAction::new("close-tab", None);
+    let win = window.downgrade();
+    close.connect_activate(move |_, _| {
+        if let Some(workspace) = win.upgrade().and_then(|w| current_workspace(&w)) {
+            close_tab(workspace.widget());
+        }
+    });
+    window.add_action(&close);
+
+    let rename = gio::SimpleAction::new("rename-tab", None);
+    let win = window.downgrade();
+    rename.connect_activate(move |_, _| {
+        let Some(notebook) = win.upgrade().and_then(|w| window_notebook(&w)) else {
+            return;
+        };
+        if let Some(label) = notebook
+            .nth_page(notebook.current_page())
+            .and_then(|page| tab_label(&notebook, &page))
+        {
+            show_rename_dialog("Rename Tab", &label, &notebook);
+        }
+    });
+    window.add_action(&rename);
+
+    // Switching wraps around at either end
+    for (name, step) in [("next-tab", 1), ("previous-tab", -1)] {
+        let action = gio::SimpleAction::new(name, None);
+        let win = window.downgrade();
+        action.connect_activate(move |_, _| {
+            let Some(notebook) = win.upgrade().and_then(|w| window_notebook(&w)) else {
+                return;
+            };
+            let count = notebook.n_pages() as i32;
+            if let Some(current) = notebook.current_page() {
+                let next = (current as i32 + step).rem_euclid(count);
+                notebook.set_current_page(Some(next as u32));
+            }
+        });
+        window.add_action(&action);
+    }
+
+    // Moving stops at the ends
+    for (name, step) in [("move-tab-right", 1), ("move-tab-left", -1)] {
+        let action = gio::SimpleAction::new(name, None);
+        let win = window.downgrade();
+        action.connect_activate(move |_, _| {
+            let Some(notebook) = win.upgrade().and_then(|w| window_notebook(&w)) else {
+                return;
+            };
+            let Some(current) = notebook.current_page() else {
+                return;
+            };
+            let target = current as i32 + step;
+            if target < 0 || target >= notebook.n_pages() as i32 {
+                return;
+            }
+            if let Some(page) = notebook.nth_page(Some(current)) {
+                notebook.reorder_child(&page, Some(target as u32));
+            }
+        });
+        window.add_action(&action);
+    }
+}
@@ -1,74 +1,258 @@
-use crate::constants::TERMINAL_COLORS;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::config::{self, Config, schemes};
 use crate::layout::persist::{load_layout, save_layout};
+use crate::layout::types::{ExitPolicy, Scrollback, TerminalLayout};
+use crate::remote::PANE_ID_ENV;
+use crate::ui::broadcast::{
+    create_indicator, set_broadcast_group, setup_broadcast, show_group_dialog,
+};
+use crate::ui::exit::{create_exit_banner, spawn_failed};
+use crate::ui::links::{link_at, open_link, resolve_path, setup_links};
+use crate::ui::scrollback::{
+    log_path, save_scrollback_dialog, start_logging, start_logging_dialog, stop_logging,
+};
+use crate::ui::search::{create_search_bar, toggle_search};
 use crate::ui::split::{split_terminal, stop_terminal};
+use crate::ui::theme;
+use crate::util::ids::new_pane_uuid;
+use crate::util::shell::shell_argv;
+use crate::util::spawn::{spawn_argv, spawn_env};
+use crate::util::zoom::{clamp_font_scale, step_font_scale};
 
-use gtk::{gio, glib}; // add gdk here
+use gtk::{gdk, gio, glib};
 use gtk4 as gtk;
 use vte4::prelude::*;
 use vte4::{PtyFlags, Terminal}; // keep this
 
-fn create_terminal_with_working_dir(working_dir: Option<&str>) -> Terminal {
-    let terminal = Terminal::new();
-
-    // Make terminal expand to fill available space
-    terminal.set_hexpand(true);
-    terminal.set_vexpand(true);
+// What a pane runs: the shell or its command, with the pane's environment
+struct PaneProcess {
+    argv: Vec<String>,
+    envv: Vec<String>,
+    working_dir: Option<String>,
+}
 
-    // Spawn bash in the terminal with specific working directory
-    let workdir = working_dir.map(|s| s.to_string());
+fn spawn_process(terminal: &Terminal, process: &PaneProcess) {
+    let argv: Vec<&str> = process.argv.iter().map(String::as_str).collect();
+    let envv: Vec<&str> = process.envv.iter().map(String::as_str).collect();
     terminal.spawn_async(
         PtyFlags::DEFAULT,
-        workdir.as_deref(),
-        &["/bin/bash", "--rcfile", "/app/etc/spliterma-rc"], // <- here
-        &[],                                                 // inherit env
-        glib::SpawnFlags::DEFAULT,
+        process.working_dir.as_deref(),
+        &argv,
+        &envv,
+        glib::SpawnFlags::SEARCH_PATH,
         || {},
         -1,
         None::<&gio::Cancellable>,
-        |res| {
-            if let Err(e) = res {
-                eprintln!("spawn failed: {e}");
+        {
+            let terminal = terminal.clone();
+            move |res| match res {
+                Ok(pid) => unsafe {
+                    terminal.set_data("child_pid", pid.0);
+                },
+                Err(e) => {
+                    eprintln!("spawn failed: {e}");
+                    spawn_failed(&terminal, e.message());
+                }
             }
         },
     );
+}
 
-    terminal
+/// Start the pane's shell or command again after it has exited.
+pub(crate) fn restart_process(terminal: &Terminal) {
+    unsafe {
+        if let Some(process) = terminal.data::<PaneProcess>("process") {
+            spawn_process(terminal, process.as_ref());
+        }
+    }
+}
+
+fn set_pane_exit_policy(terminal: &Terminal, policy: Option<ExitPolicy>) {
+    unsafe {
+        match policy {
+            Some(policy) => terminal.set_data("on_exit", policy),
+            None => drop(terminal.steal_data::<ExitPolicy>("on_exit")),
+        }
+    }
 }
 
-fn _create_terminal() -> Terminal {
-    create_terminal_with_working_dir(None)
+/// The pane's own exit policy, if it doesn't follow the config.
+pub(crate) fn pane_exit_policy(terminal: &Terminal) -> Option<ExitPolicy> {
+    unsafe { terminal.data::<ExitPolicy>("on_exit").map(|p| *p.as_ref()) }
 }
 
-pub(crate) fn create_terminal_with_title(
-    title: &str,
-    color_index: usize,
+fn create_terminal_with_working_dir(
     working_dir: Option<&str>,
-) -> gtk::Box {
+    pane: &TerminalLayout,
+    pane_uuid: &str,
+) -> Terminal {
+    let terminal = Terminal::new();
+    let config = config::get();
+
+    // Make terminal expand to fill available space
+    terminal.set_hexpand(true);
+    terminal.set_vexpand(true);
+    if let Some(id) = &pane.color_scheme {
+        unsafe {
+            terminal.set_data("color_scheme", id.clone());
+        }
+    }
+    if let Some(scrollback) = pane.scrollback_lines {
+        unsafe {
+            terminal.set_data("scrollback_lines", scrollback);
+        }
+    }
+    apply_terminal_config(&terminal, &config);
+    if let Some(scale) = pane.font_scale {
+        terminal.set_font_scale(clamp_font_scale(scale));
+    }
+
+    set_pane_exit_policy(&terminal, pane.on_exit);
+
+    // Spawn the shell (or the pane's startup command) with specific working directory
+    let shell = shell_argv(config.terminal.shell.as_deref());
+    let mut envv = spawn_env(&pane.env); // added on top of the inherited env
+    // Lets scripts in the pane address it through spliterma-ctl
+    envv.push(format!("{PANE_ID_ENV}={pane_uuid}"));
+    let process = PaneProcess {
+        argv: spawn_argv(shell, pane.command.as_deref(), pane.keep_shell),
+        envv,
+        working_dir: working_dir.map(|s| s.to_string()),
+    };
+    spawn_process(&terminal, &process);
+    // Kept for restarts after the process exits
+    unsafe {
+        terminal.set_data("process", process);
+    }
+
+    terminal
+}
+
+/// Settings from config.toml that can change while the terminal is running.
+pub(crate) fn apply_terminal_config(terminal: &Terminal, config: &Config) {
+    let font = config
+        .terminal
+        .font
+        .as_deref()
+        .map(gtk::pango::FontDescription::from_string);
+    terminal.set_font(font.as_ref());
+
+    terminal.set_scrollback_lines(scrollback_lines(terminal, config));
+    apply_color_scheme(terminal, config);
+}
+
+// VTE's own default when neither the pane nor the config says otherwise
+const DEFAULT_SCROLLBACK_LINES: i64 = 512;
+
+fn scrollback_lines(terminal: &Terminal, config: &Config) -> i64 {
+    pane_scrollback(terminal)
+        .or(config.terminal.scrollback_lines)
+        .map_or(DEFAULT_SCROLLBACK_LINES, Scrollback::vte_lines)
+}
+
+/// The pane's own scrollback size, if it doesn't follow the config.
+pub(crate) fn pane_scrollback(terminal: &Terminal) -> Option<Scrollback> {
+    unsafe {
+        terminal
+            .data::<Scrollback>("scrollback_lines")
+            .map(|s| *s.as_ref())
+    }
+}
+
+/// Drop everything above the screen; the size limit stays as it was.
+pub(crate) fn clear_scrollback(terminal: &Terminal) {
+    let lines = terminal.scrollback_lines();
+    terminal.set_scrollback_lines(0);
+    terminal.set_scrollback_lines(lines);
+}
+
+/// Zoom the pane's font in or out by one step; `None` goes back to 100%.
+pub(crate) fn zoom_terminal(terminal: &Terminal, zoom_in: Option<bool>) {
+    let scale = zoom_in.map_or(1.0, |zoom_in| {
+        step_font_scale(terminal.font_scale(), zoom_in)
+    });
+    terminal.set_font_scale(scale);
+}
+
+/// The pane's own colour scheme, if it doesn't follow the config.
+pub(crate) fn pane_color_scheme(terminal: &Terminal) -> Option<String> {
+    unsafe {
+        terminal
+            .data::<String>("color_scheme")
+            .map(|id| id.as_ref().clone())
+    }
+}
+
+fn set_pane_color_scheme(terminal: &Terminal, id: Option<String>) {
+    unsafe {
+        match id {
+            Some(id) => terminal.set_data("color_scheme", id),
+            None => drop(terminal.steal_data::<String>("color_scheme")),
+        }
+    }
+    apply_color_scheme(terminal, &config::get());
+}
+
+// The pane's scheme wins over the global one; neither means VTE's defaults
+fn apply_color_scheme(terminal: &Terminal, config: &Config) {
+    let pane_scheme = pane_color_scheme(terminal);
+    let scheme = pane_scheme
+        .as_deref()
+        .and_then(|id| {
+            let scheme = schemes::find(id);
+            if scheme.is_none() {
+                eprintln!("Unknown colour scheme '{id}', using the default");
+            }
+            scheme
+        })
+        .or_else(|| {
+            config
+                .terminal
+                .color_scheme
+                .as_deref()
+                .and_then(schemes::find)
+        });
+
+    let Some(scheme) = scheme else {
+        terminal.set_colors(None, None, &[]);
+        terminal.set_color_cursor(None);
+        return;
+    };
+    // Colours were validated when the scheme was loaded
+    let rgba = |c: &str| gdk::RGBA::parse(c).unwrap_or(gdk::RGBA::BLACK);
+    let palette: Vec<gdk::RGBA> = scheme.palette.iter().map(|c| rgba(c)).collect();
+    let palette: Vec<&gdk::RGBA> = palette.iter().collect();
+    terminal.set_colors(
+        Some(&rgba(&scheme.foreground)),
+        Some(&rgba(&scheme.background)),
+        &palette,
+    );
+    terminal.set_color_cursor(scheme.cursor.as_deref().map(rgba).as_ref());
+}
+
+pub(crate) fn create_terminal_with_title(pane: &TerminalLayout) -> gtk::Box {
+    let title = pane.name.as_str();
+    let color = config::get().pane_accent(pane.color.as_deref(), pane.color_index);
+    let working_dir = Some(pane.working_dir.as_str()).filter(|d| !d.is_empty());
+
     // Create container for title + terminal
     let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
     container.set_hexpand(true);
     container.set_vexpand(true);
 
+    let pane_uuid = pane.id.clone().unwrap_or_else(new_pane_uuid);
     unsafe {
-        container.set_data("color_index", color_index);
+        container.set_data("color", color.clone());
+        container.set_data("pane_uuid", pane_uuid.clone());
     }
 
     // Create title bar
     let title_bar = gtk::Box::new(gtk::Orientation::Horizontal, 8);
 
-    // Set title bar background color
-    let color = TERMINAL_COLORS[color_index % TERMINAL_COLORS.len()];
     title_bar.set_css_classes(&["terminal-title"]);
 
-    let css_provider = gtk::CssProvider::new();
-    css_provider.load_from_data(&format!(
-        ".terminal-title {{ background-color: {}; border-radius: 6px; min-height: 30px; }}",
-        color
-    ));
-
-    let style_context = title_bar.style_context();
-    style_context.add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
-
     // Create color picker button
     let color_button = gtk::Button::new();
     color_button.set_css_classes(&["flat"]);
@@ -77,30 +261,17 @@ pub(crate) fn create_terminal_with_title(
     color_icon.set_size_request(16, 16);
     color_icon.set_css_classes(&["color-dot"]);
 
-    let color_css = gtk::CssProvider::new();
-    color_css.load_from_data(&format!(
-        ".color-dot {{ background-color: {}; border-radius: 50%; min-width: 16px; min-height: 16px; border: 2px solid white; }}",
-        color
-    ));
-    color_icon
-        .style_context()
-        .add_provider(&color_css, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
-
     color_button.set_child(Some(&color_icon));
 
+    // Set title bar background color (the dot follows it)
+    theme::set_accent(&title_bar, &color);
+
     // Create editable title label
     let title_label = gtk::Label::new(Some(title));
     title_label.set_hexpand(true);
     title_label.set_halign(gtk::Align::Start);
     title_label.set_css_classes(&["terminal-title-text"]);
 
-    let title_css = gtk::CssProvider::new();
-    title_css
-        .load_from_data(".terminal-title-text { color: white; font-weight: bold; padding: 4px; }");
-    title_label
-        .style_context()
-        .add_provider(&title_css, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
-
     // Add double-click to edit title
     let title_gesture = gtk::GestureClick::new();
     title_gesture.set_button(1); // Left click
@@ -110,16 +281,18 @@ pub(crate) fn create_terminal_with_title(
     title_gesture.connect_pressed(move |_gesture, n_press, _x, _y| {
         if n_press == 2 {
             // Double click
-            show_rename_dialog(&title_label_clone, &title_bar_clone);
+            show_rename_dialog("Rename Terminal", &title_label_clone, &title_bar_clone);
         }
     });
     title_label.add_controller(title_gesture);
 
     title_bar.append(&color_button);
     title_bar.append(&title_label);
+    title_bar.append(&create_indicator(&container));
+    set_broadcast_group(&container, pane.broadcast_group.as_deref());
 
     // Create terminal
-    let terminal = create_terminal_with_working_dir(working_dir);
+    let terminal = create_terminal_with_working_dir(working_dir, pane, &pane_uuid);
 
     terminal.set_can_focus(true);
     terminal.set_focusable(true);
@@ -140,22 +313,60 @@ pub(crate) fn create_terminal_with_title(
     }
 
     // Set up color picker popup
-    setup_color_picker(&color_button, &title_bar, &color_icon, &container);
+    setup_color_picker(&color_button, &title_bar, &container);
+
+    // Ctrl+click on URLs and file:line references
+    setup_links(&terminal);
+    setup_broadcast(&terminal, &container);
+
+    if let Some(log_file) = &pane.log_file {
+        let path = resolve_path(log_file, working_dir, &glib::home_dir());
+        if let Err(e) = start_logging(&terminal, &path) {
+            eprintln!("Not logging pane '{title}': {e}");
+        }
+    }
 
     // Set up context menu for the terminal
     setup_context_menu(&terminal, &container);
 
+    // Scrollbar to the right of the terminal, sharing its adjustment
+    let scrollbar =
+        gtk::Scrollbar::new(gtk::Orientation::Vertical, terminal.vadjustment().as_ref());
+    let terminal_row = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+    terminal_row.append(&terminal);
+    terminal_row.append(&scrollbar);
+
     container.append(&title_bar);
-    container.append(&terminal);
+    container.append(&create_search_bar(&terminal));
+    // Shown when the process exits and the pane is held open
+    container.append(&create_exit_banner(&terminal, &container));
+    container.append(&terminal_row);
 
     container
 }
 
+pub(crate) fn find_terminal_in_container(container: &gtk::Box) -> Option<Terminal> {
+    // Look through container children to find the terminal (it sits in a row with its scrollbar)
+    let mut child = container.first_child();
+    while let Some(widget) = child {
+        if let Ok(terminal) = widget.clone().downcast::<Terminal>() {
+            return Some(terminal);
+        }
+        if let Some(terminal) = widget
+            .first_child()
+            .and_then(|w| w.downcast::<Terminal>().ok())
+        {
+            return Some(terminal);
+        }
+        child = widget.next_sibling();
+    }
+    None
+}
+
 fn setup_color_picker(
     color_button: &gtk::Button,
     title_bar: &gtk::Box,
-    color_icon: &gtk::Box,
-    container: &gtk::Box, // we store the chosen color index here
+    container: &gtk::Box, // we store the chosen color here
 ) {
     let popover = gtk::Popover::new();
     popover.set_parent(color_button);
@@ -168,67 +379,165 @@ fn setup_color_picker(
     color_grid.set_margin_top(12);
     color_grid.set_margin_bottom(12);
 
-    for (i, &color) in TERMINAL_COLORS.iter().enumerate() {
+    popover.set_child(Some(&color_grid));
+
+    // Built on every open so palette changes in config.toml show up
+    let title_bar = title_bar.clone();
+    let container = container.clone();
+    color_button.connect_clicked(move |_| {
+        fill_color_grid(&color_grid, &popover, &title_bar, &container);
+        popover.popup();
+    });
+}
+
+fn fill_color_grid(
+    color_grid: &gtk::Grid,
+    popover: &gtk::Popover,
+    title_bar: &gtk::Box,
+    container: &gtk::Box,
+) {
+    while let Some(child) = color_grid.first_child() {
+        color_grid.remove(&child);
+    }
+
+    let config = config::get();
+    for (i, color) in config.terminal.palette.iter().enumerate() {
         let color_btn = gtk::Button::new();
         color_btn.set_size_request(24, 24);
 
         let color_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
         color_box.set_css_classes(&["color-picker-dot"]);
-
-        let picker_css = gtk::CssProvider::new();
-        picker_css.load_from_data(&format!(
-            ".color-picker-dot {{ background-color: {}; border-radius: 50%; min-width: 20px; min-height: 20px; border: 2px solid #ccc; }}",
-            color
-        ));
-        color_box
-            .style_context()
-            .add_provider(&picker_css, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+        theme::set_accent(&color_box, color);
 
         color_btn.set_child(Some(&color_box));
         color_btn.set_css_classes(&["flat"]);
 
         let title_bar_clone = title_bar.clone();
-        let color_icon_clone = color_icon.clone();
         let popover_clone = popover.clone();
         let color_str = color.to_string();
-        let container_clone = container.clone(); // <-- needed to write color_index
+        let container_clone = container.clone(); // <-- needed to write the color
 
         color_btn.connect_clicked(move |_| {
-            // Update title bar background
-            let css_provider = gtk::CssProvider::new();
-            css_provider.load_from_data(&format!(
-                ".terminal-title {{ background-color: {}; border-radius: 6px; min-height: 30px; }}",
-                color_str
-            ));
-            title_bar_clone.style_context().add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
-
-            // Update color icon
-            let color_css = gtk::CssProvider::new();
-            color_css.load_from_data(&format!(
-                ".color-dot {{ background-color: {}; border-radius: 50%; min-width: 16px; min-height: 16px; border: 2px solid white; }}",
-                color_str
-            ));
-            color_icon_clone.style_context().add_provider(&color_css, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
-
-            // *** persist the chosen color index for this terminal ***
-            unsafe { container_clone.set_data("color_index", i); }
-
+            set_pane_accent(&container_clone, &title_bar_clone, &color_str);
             popover_clone.popdown();
         });
 
         color_grid.attach(&color_btn, (i % 4) as i32, (i / 4) as i32, 1, 1);
     }
 
-    popover.set_child(Some(&color_grid));
+    // Anything outside the palette
+    let custom_btn = gtk::Button::with_label("Custom…");
+    custom_btn.add_css_class("flat");
+    let rows = config.terminal.palette.len().div_ceil(4) as i32;
+    color_grid.attach(&custom_btn, 0, rows, 4, 1);
 
-    color_button.connect_clicked(move |_| {
-        popover.popup();
+    let popover_clone = popover.clone();
+    let title_bar = title_bar.clone();
+    let container = container.clone();
+    custom_btn.connect_clicked(move |_| {
+        popover_clone.popdown();
+        choose_custom_color(&container, &title_bar);
     });
 }
 
-fn show_rename_dialog(title_label: &gtk::Label, title_bar: &gtk::Box) {
+fn choose_custom_color(container: &gtk::Box, title_bar: &gtk::Box) {
+    let window = container
+        .root()
+        .and_then(|r| r.downcast::<gtk::Window>().ok());
+    let current = unsafe {
+        container
+            .data::<String>("color")
+            .map(|c| c.as_ref().clone())
+    };
+    let initial = current.and_then(|c| gdk::RGBA::parse(c.as_str()).ok());
+
+    let dialog = gtk::ColorDialog::builder()
+        .title("Pane Colour")
+        .modal(true)
+        .with_alpha(false)
+        .build();
+    let container = container.clone();
+    let title_bar = title_bar.clone();
+    dialog.choose_rgba(
+        window.as_ref(),
+        initial.as_ref(),
+        gio::Cancellable::NONE,
+        move |result| {
+            // Cancelling the dialog is an error too
+            if let Ok(rgba) = result {
+                set_pane_accent(&container, &title_bar, &hex_color(&rgba));
+            }
+        },
+    );
+}
+
+fn set_pane_accent(container: &gtk::Box, title_bar: &gtk::Box, color: &str) {
+    theme::set_accent(title_bar, color);
+    unsafe {
+        container.set_data("color", color.to_string());
+    }
+}
+
+// The title bar is the first child of the pane container
+fn title_bar_of(container: &gtk::Box) -> Option<gtk::Box> {
+    container.first_child()?.downcast::<gtk::Box>().ok()
+}
+
+/// Recolour a pane's title bar from outside the colour picker.
+pub(crate) fn set_pane_color(container: &gtk::Box, color: &str) -> Result<(), String> {
+    if !config::is_hex_color(color) {
+        return Err(format!("'{color}' is not a #RGB or #RRGGBB colour"));
+    }
+    let title_bar = title_bar_of(container).ok_or("Pane has no title bar")?;
+    set_pane_accent(container, &title_bar, color);
+    Ok(())
+}
+
+pub(crate) fn set_pane_title(container: &gtk::Box, title: &str) -> Result<(), String> {
+    let mut child = title_bar_of(container).and_then(|bar| bar.first_child());
+    while let Some(widget) = child {
+        if let Ok(label) = widget.clone().downcast::<gtk::Label>() {
+            label.set_text(title);
+            return Ok(());
+        }
+        child = widget.next_sibling();
+    }
+    Err("Pane has no title".into())
+}
+
+/// Process id of the pane's shell (or command), once it has started.
+pub(crate) fn child_pid(terminal: &Terminal) -> Option<i32> {
+    unsafe { terminal.data::<i32>("child_pid").map(|pid| *pid.as_ref()) }
+}
+
+/// Send SIGHUP to the pane's process, as closing a terminal window does.
+pub(crate) fn hang_up(terminal: &Terminal) {
+    if let Some(pid) = child_pid(terminal) {
+        unsafe {
+            libc::kill(pid, libc::SIGHUP);
+        }
+    }
+}
+
+fn hex_color(rgba: &gdk::RGBA) -> String {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(rgba.red()),
+        channel(rgba.green()),
+        channel(rgba.blue())
+    )
+}
+
+// gtk::Dialog is deprecated since GTK 4.10
+#[allow(deprecated)]
+pub(crate) fn show_rename_dialog(
+    dialog_title: &str,
+    title_label: &gtk::Label,
+    title_bar: &impl IsA<gtk::Widget>,
+) {
     let dialog = gtk::Dialog::builder()
-        .title("Rename Terminal")
+        .title(dialog_title)
         .modal(true)
         .build();
 
@@ -280,10 +589,39 @@ fn setup_context_menu(terminal: &Terminal, container: &gtk::Box) {
     let menu = gio::Menu::new();
     menu.append(Some("Copy"), Some("terminal.copy")); // Add copy option
     menu.append(Some("Paste"), Some("terminal.paste")); // Add paste option
+    let link_section = gio::Menu::new();
+    link_section.append(Some("Open Link"), Some("terminal.open-link"));
+    link_section.append(Some("Copy Link"), Some("terminal.copy-link"));
+    menu.append_section(None, &link_section);
+    menu.append(Some("Find…"), Some("terminal.find"));
+    let scrollback_section = gio::Menu::new();
+    scrollback_section.append(
+        Some("Save Scrollback As…"),
+        Some("terminal.save-scrollback"),
+    );
+    scrollback_section.append(Some("Clear Scrollback"), Some("terminal.clear-scrollback"));
+    scrollback_section.append(Some("Log to File…"), Some("terminal.start-log"));
+    scrollback_section.append(Some("Stop Logging"), Some("terminal.stop-log"));
+    menu.append_section(None, &scrollback_section);
     menu.append(Some("Split Horizontal"), Some("split.horizontal"));
     menu.append(Some("Split Vertical"), Some("split.vertical"));
+    menu.append(Some("Broadcast Group…"), Some("terminal.broadcast-group"));
     menu.append(Some("Save Layout"), Some("terminal.save-layout"));
     menu.append(Some("Load Layout"), Some("terminal.load-layout"));
+    let scheme_menu = gio::Menu::new();
+    menu.append_submenu(Some("Colour Scheme"), &scheme_menu);
+    let exit_menu = gio::Menu::new();
+    for (label, id) in [
+        ("Default", ""),
+        ("Close Pane", "close"),
+        ("Keep Open", "hold"),
+        ("Restart", "restart"),
+    ] {
+        let item = gio::MenuItem::new(Some(label), None);
+        item.set_action_and_target_value(Some("terminal.on-exit"), Some(&id.to_variant()));
+        exit_menu.append_item(&item);
+    }
+    menu.append_submenu(Some("When Process Exits"), &exit_menu);
     menu.append(Some("Close Terminal"), Some("terminal.stop"));
 
     let popover_menu = gtk::PopoverMenu::from_model(Some(&menu));
@@ -314,6 +652,64 @@ fn setup_context_menu(terminal: &Terminal, container: &gtk::Box) {
     });
     action_group.add_action(&paste_action);
 
+    // --- Links: the one under the pointer when the menu was opened
+    let link = Rc::new(RefCell::new(None::<String>));
+    let open_link_action = gio::SimpleAction::new("open-link", None);
+    let copy_link_action = gio::SimpleAction::new("copy-link", None);
+    let terminal_for_link = terminal.clone();
+    let link_clone = link.clone();
+    open_link_action.connect_activate(move |_, _| {
+        if let Some(link) = link_clone.borrow().as_deref() {
+            open_link(&terminal_for_link, link);
+        }
+    });
+    let terminal_for_link = terminal.clone();
+    let link_clone = link.clone();
+    copy_link_action.connect_activate(move |_, _| {
+        if let Some(link) = link_clone.borrow().as_deref() {
+            terminal_for_link.clipboard().set_text(link);
+        }
+    });
+    action_group.add_action(&open_link_action);
+    action_group.add_action(&copy_link_action);
+
+    // --- Scrollback export and logging
+    let terminal_for_save = terminal.clone();
+    let save_scrollback_action = gio::SimpleAction::new("save-scrollback", None);
+    save_scrollback_action.connect_activate(move |_, _| save_scrollback_dialog(&terminal_for_save));
+    action_group.add_action(&save_scrollback_action);
+
+    let terminal_for_clear = terminal.clone();
+    let clear_scrollback_action = gio::SimpleAction::new("clear-scrollback", None);
+    clear_scrollback_action.connect_activate(move |_, _| clear_scrollback(&terminal_for_clear));
+    action_group.add_action(&clear_scrollback_action);
+
+    let terminal_for_log = terminal.clone();
+    let start_log_action = gio::SimpleAction::new("start-log", None);
+    start_log_action.connect_activate(move |_, _| start_logging_dialog(&terminal_for_log));
+    action_group.add_action(&start_log_action);
+
+    let terminal_for_log = terminal.clone();
+    let stop_log_action = gio::SimpleAction::new("stop-log", None);
+    stop_log_action.connect_activate(move |_, _| stop_logging(&terminal_for_log));
+    action_group.add_action(&stop_log_action);
+
+    // --- Broadcast
+    let container_for_group = container.clone();
+    let group_action = gio::SimpleAction::new("broadcast-group", None);
+    group_action.connect_activate(move |_, _| show_group_dialog(&container_for_group));
+    action_group.add_action(&group_action);
+
+    // --- Search
+    let container_for_find = container.clone();
+    let popover_for_find = popover_menu.clone();
+    let find_action = gio::SimpleAction::new("find", None);
+    find_action.connect_activate(move |_, _| {
+        popover_for_find.popdown();
+        toggle_search(&container_for_find);
+    });
+    action_group.add_action(&find_action);
+
     // --- Horizontal split
     let container_clone = container.clone();
     let popover_clone = popover_menu.clone();
@@ -343,11 +739,7 @@ fn setup_context_menu(terminal: &Terminal, container: &gtk::Box) {
             .root()
             .and_then(|r| r.downcast::<gtk::ApplicationWindow>().ok())
         {
-            if let Some(child) = win.child() {
-                save_layout(&child);
-            } else {
-                eprintln!("Window has no child to save");
-            }
+            save_layout(&win);
         } else {
             eprintln!("Could not find ApplicationWindow for saving");
         }
@@ -383,6 +775,34 @@ fn setup_context_menu(terminal: &Terminal, container: &gtk::Box) {
     });
     action_group.add_action(&stop_action);
 
+    // --- Colour scheme ("" follows the config)
+    let terminal_for_scheme = terminal.clone();
+    let popover_for_scheme = popover_menu.clone();
+    let scheme_action =
+        gio::SimpleAction::new_stateful("color-scheme", Some(glib::VariantTy::STRING), &"".into());
+    scheme_action.connect_change_state(move |action, value| {
+        let Some(id) = value.and_then(|v| v.get::<String>()) else {
+            return;
+        };
+        action.set_state(&id.to_variant());
+        set_pane_color_scheme(&terminal_for_scheme, Some(id).filter(|id| !id.is_empty()));
+        popover_for_scheme.popdown();
+    });
+    action_group.add_action(&scheme_action);
+
+    // --- Exit policy ("" follows the config)
+    let terminal_for_exit = terminal.clone();
+    let exit_action =
+        gio::SimpleAction::new_stateful("on-exit", Some(glib::VariantTy::STRING), &"".into());
+    exit_action.connect_change_state(move |action, value| {
+        let Some(id) = value.and_then(|v| v.get::<String>()) else {
+            return;
+        };
+        action.set_state(&id.to_variant());
+        set_pane_exit_policy(&terminal_for_exit, ExitPolicy::from_id(&id));
+    });
+    action_group.add_action(&exit_action);
+
     // Expose actions under both prefixes
     terminal.insert_action_group("split", Some(&action_group));
     terminal.insert_action_group("terminal", Some(&action_group));
@@ -391,11 +811,39 @@ fn setup_context_menu(terminal: &Terminal, container: &gtk::Box) {
     let gesture = gtk::GestureClick::new();
     gesture.set_button(3);
     let popover_menu_clone = popover_menu.clone();
+    let terminal_clone3 = terminal.clone();
     gesture.connect_pressed(move |gesture, _n_press, x, y| {
         let _widget = gesture.widget();
+        let link_here = link_at(&terminal_clone3, x, y);
+        open_link_action.set_enabled(link_here.is_some());
+        copy_link_action.set_enabled(link_here.is_some());
+        link.replace(link_here);
+        let logging = log_path(&terminal_clone3).is_some();
+        start_log_action.set_enabled(!logging);
+        stop_log_action.set_enabled(logging);
+
+        // User schemes may have changed since the last time
+        fill_scheme_menu(&scheme_menu);
+        let current = pane_color_scheme(&terminal_clone3).unwrap_or_default();
+        scheme_action.set_state(&current.to_variant());
+        let on_exit = pane_exit_policy(&terminal_clone3).map_or("", |p| p.id());
+        exit_action.set_state(&on_exit.to_variant());
         let rect = gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1);
         popover_menu_clone.set_pointing_to(Some(&rect));
         popover_menu_clone.popup();
     });
     terminal.add_controller(gesture);
 }
+
+fn fill_scheme_menu(menu: &gio::Menu) {
+    menu.remove_all();
+    let item = |label: &str, id: &str| {
+        let item = gio::MenuItem::new(Some(label), None);
+        item.set_action_and_target_value(Some("terminal.color-scheme"), Some(&id.to_variant()));
+        item
+    };
+    menu.append_item(&item("Default", ""));
+    for scheme in schemes::all().iter() {
+        menu.append_item(&item(&scheme.name, &scheme.id));
+    }
+}
@@ -0,0 +1,179 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use gtk::gdk;
+use gtk::prelude::*;
+use gtk4 as gtk;
+
+use crate::config::config_dir;
+
+const BASE_CSS: &str = "
+.terminal-title { border-radius: 6px; min-height: 30px; }
+.terminal-title-text { color: white; font-weight: bold; padding: 4px; }
+.color-dot { border-radius: 50%; min-width: 16px; min-height: 16px; border: 2px solid white; }
+.terminal-title.broadcasting { box-shadow: inset 0 0 0 2px #e01b24; }
+.broadcast-indicator { color: white; padding: 0 4px; }
+.exit-banner { padding: 4px 8px; border-bottom: 1px solid alpha(currentColor, 0.15); }
+.color-picker-dot { border-radius: 50%; min-width: 20px; min-height: 20px; border: 2px solid #ccc; }
+";
+
+// One provider each for the fixed rules, the accent colours in use and the user's style.css
+struct Theme {
+    accents: gtk::CssProvider,
+    user: gtk::CssProvider,
+    colors: BTreeSet<String>,
+}
+
+thread_local! {
+    static THEME: RefCell<Option<Theme>> = const { RefCell::new(None) };
+}
+
+pub fn style_path() -> PathBuf {
+    config_dir().join("style.css")
+}
+
+/// Install the stylesheets on the default display. Call once GTK is up.
+pub fn init() {
+    let Some(display) = gdk::Display::default() else {
+        eprintln!("No display, not installing styles");
+        return;
+    };
+
+    let base = gtk::CssProvider::new();
+    base.load_from_data(BASE_CSS);
+    let accents = gtk::CssProvider::new();
+    let user = gtk::CssProvider::new();
+    user.connect_parsing_error(|_, section, error| {
+        eprintln!(
+            "style.css:{}: {error}",
+            section.start_location().lines() + 1
+        );
+    });
+
+    gtk::style_context_add_provider_for_display(
+        &display,
+        &base,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+    gtk::style_context_add_provider_for_display(
+        &display,
+        &accents,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+    // USER priority, so style.css wins over everything above
+    gtk::style_context_add_provider_for_display(&display, &user, gtk::STYLE_PROVIDER_PRIORITY_USER);
+
+    THEME.with(|t| {
+        *t.borrow_mut() = Some(Theme {
+            accents,
+            user,
+            colors: BTreeSet::new(),
+        })
+    });
+    load_user_css();
+}
+
+/// Re-read `style.css`; a missing file clears the user styles.
+pub fn load_user_css() {
+    let css = match std::fs::read_to_string(style_path()) {
+        Ok(css) => css,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            eprintln!("{}: {e}", style_path().display());
+            return;
+        }
+    };
+    THEME.with(|t| {
+        if let Some(theme) = t.borrow().as_ref() {
+            theme.user.load_from_data(&css);
+        }
+    });
+}
+
+/// Give `widget` the accent colour, replacing any previous one.
+/// Title bars and palette dots both use this; the dot inside a title bar follows it.
+pub fn set_accent(widget: &impl IsA<gtk::Widget>, color: &str) {
+    let Some(class) = accent_class(color) else {
+        eprintln!("Ignoring invalid accent colour '{color}'");
+        return;
+    };
+    register_color(&class);
+
+    for old in widget.css_classes() {
+        if old.starts_with(ACCENT_PREFIX) && old != class {
+            widget.remove_css_class(&old);
+        }
+    }
+    widget.add_css_class(&class);
+}
+
+const ACCENT_PREFIX: &str = "accent-";
+
+// Rules are only generated for colours that are actually used
+fn register_color(class: &str) {
+    THEME.with(|t| {
+        let mut theme = t.borrow_mut();
+        let Some(theme) = theme.as_mut() else {
+            return;
+        };
+        if theme.colors.insert(class.to_string()) {
+            theme.accents.load_from_data(&accent_css(&theme.colors));
+        }
+    });
+}
+
+/// CSS class for a #RGB or #RRGGBB colour, e.g. "accent-3584e4".
+pub fn accent_class(color: &str) -> Option<String> {
+    let hex = color.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let hex = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    Some(format!("{ACCENT_PREFIX}{}", hex.to_ascii_lowercase()))
+}
+
+pub fn accent_css<'a>(classes: impl IntoIterator<Item = &'a String>) -> String {
+    classes
+        .into_iter()
+        .filter_map(|class| {
+            let hex = class.strip_prefix(ACCENT_PREFIX)?;
+            Some(format!(
+                ".{class}, .{class} .color-dot {{ background-color: #{hex}; }}\n"
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accent_classes() {
+        assert_eq!(accent_class("#3584E4").as_deref(), Some("accent-3584e4"));
+        assert_eq!(accent_class("#0f0").as_deref(), Some("accent-00ff00"));
+        assert_eq!(accent_class("3584e4"), None);
+        assert_eq!(accent_class("#12345"), None);
+        assert_eq!(accent_class("#zzzzzz"), None);
+    }
+
+    #[test]
+    fn one_rule_per_colour() {
+        let classes: BTreeSet<String> = ["#3584e4", "#3584E4", "#0f0"]
+            .iter()
+            .filter_map(|c| accent_class(c))
+            .collect();
+        let css = accent_css(&classes);
+        assert_eq!(css.lines().count(), 2);
+        assert!(
+            css.contains(
+                ".accent-00ff00, .accent-00ff00 .color-dot { background-color: #00ff00; }"
+            )
+        );
+    }
+}
@@ -0,0 +1,459 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gtk::gio;
+use gtk::prelude::*;
+use gtk4 as gtk;
+
+use crate::layout::tree::{Direction, LayoutNode, LayoutTree, PaneId, divider_fractions};
+use crate::layout::types::{SplitType, TerminalLayout};
+use crate::ui::tabs::{window_notebook, workspaces};
+use crate::ui::terminal::{create_terminal_with_title, find_terminal_in_container};
+use crate::util::ids::new_pane_uuid;
+
+/// GTK view of a `LayoutTree`. Pane containers are created once per pane and
+/// reparented into a fresh set of `gtk::Paned` every time the tree changes shape.
+#[derive(Clone)]
+pub struct Workspace {
+    root: gtk::Box,
+    inner: Rc<WorkspaceInner>,
+}
+
+// Owned by the root widget (its "workspace" data), so it goes away with the
+// widget; it must not hold the root itself or the two keep each other alive
+struct WorkspaceInner {
+    tree: RefCell<LayoutTree>,
+    panes: RefCell<HashMap<PaneId, gtk::Box>>,
+    // Paned chains of the current rendering, keyed by the path of their split
+    paneds: RefCell<Vec<(Vec<usize>, Vec<gtk::Paned>)>>,
+    // Id of the pane whose terminal had focus last
+    focused: RefCell<Option<String>>,
+}
+
+impl Workspace {
+    pub fn new(tree: LayoutTree) -> Self {
+        let root = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        root.set_hexpand(true);
+        root.set_vexpand(true);
+
+        let workspace = Workspace {
+            root: root.clone(),
+            inner: Rc::new(WorkspaceInner {
+                tree: RefCell::new(tree),
+                panes: RefCell::new(HashMap::new()),
+                paneds: RefCell::new(Vec::new()),
+                focused: RefCell::new(None),
+            }),
+        };
+
+        let ids = workspace.inner.tree.borrow().pane_ids();
+        for id in ids {
+            let pane = workspace.inner.tree.borrow().pane(id).cloned();
+            if let Some(pane) = pane {
+                workspace.add_container(id, &pane);
+            }
+        }
+
+        unsafe {
+            root.set_data("workspace", workspace.inner.clone());
+        }
+
+        workspace.render();
+        workspace
+    }
+
+    /// Find the workspace a widget belongs to by walking up its ancestors.
+    pub fn for_widget(widget: &impl IsA<gtk::Widget>) -> Option<Workspace> {
+        let mut current = Some(widget.clone().upcast::<gtk::Widget>());
+        while let Some(widget) = current {
+            if let Some(inner) = unsafe { widget.data::<Rc<WorkspaceInner>>("workspace") } {
+                let inner = unsafe { inner.as_ref() }.clone();
+                let root = widget.downcast::<gtk::Box>().ok()?;
+                return Some(Workspace { root, inner });
+            }
+            current = widget.parent();
+        }
+        None
+    }
+
+    /// Detach the workspace from its widget now instead of when the widget is freed,
+    /// so its panes go even if something still holds the widget.
+    pub fn release(&self) {
+        unsafe {
+            let _ = self.root.steal_data::<Rc<WorkspaceInner>>("workspace");
+        }
+    }
+
+    pub fn widget(&self) -> &gtk::Box {
+        &self.root
+    }
+
+    pub fn containers(&self) -> Vec<gtk::Box> {
+        self.inner.panes.borrow().values().cloned().collect()
+    }
+
+    /// Pane ids in layout order.
+    pub fn pane_ids(&self) -> Vec<PaneId> {
+        self.inner.tree.borrow().pane_ids()
+    }
+
+    pub fn container(&self, id: PaneId) -> Option<gtk::Box> {
+        self.inner.panes.borrow().get(&id).cloned()
+    }
+
+    /// Current model, with split ratios synced from the live dividers.
+    pub fn snapshot(&self) -> LayoutTree {
+        self.sync_ratios();
+        self.inner.tree.borrow().clone()
+    }
+
+    pub fn split(
+        &self,
+        target: PaneId,
+        orientation: gtk::Orientation,
+        pane: TerminalLayout,
+    ) -> Option<PaneId> {
+        self.sync_ratios();
+        let id =
+            self.inner
+                .tree
+                .borrow_mut()
+                .split(target, split_type_of(orientation), pane.clone())?;
+
+        self.add_container(id, &pane);
+        self.render();
+        self.focus(id);
+        Some(id)
+    }
+
+    /// Close a pane. Returns true when it was the last one.
+    pub fn close(&self, target: PaneId) -> bool {
+        self.sync_ratios();
+        let ids = self.inner.tree.borrow().pane_ids();
+        let next_focus = ids
+            .iter()
+            .position(|&id| id == target)
+            .and_then(|i| ids.get(i.saturating_sub(1)).or(ids.get(i + 1)))
+            .copied()
+            .filter(|&id| id != target);
+
+        if !self.inner.tree.borrow_mut().close(target) {
+            return false;
+        }
+        self.inner.panes.borrow_mut().remove(&target);
+        self.render();
+
+        if let Some(id) = next_focus {
+            self.focus(id);
+        }
+        self.inner.tree.borrow().is_empty()
+    }
+
+    /// Focus the pane next to `from` on the given side. False when there is none.
+    pub fn focus_neighbour(&self, from: PaneId, direction: Direction) -> bool {
+        self.sync_ratios();
+        let neighbour = self.inner.tree.borrow().neighbour(from, direction);
+        match neighbour {
+            Some(id) => {
+                self.focus(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Focus the next (or, for a negative step, previous) pane in reading order.
+    pub fn focus_cycle(&self, from: PaneId, step: isize) {
+        let ids = self.inner.tree.borrow().pane_ids();
+        if let Some(i) = ids.iter().position(|&id| id == from) {
+            let next = (i as isize + step).rem_euclid(ids.len() as isize) as usize;
+            self.focus(ids[next]);
+        }
+    }
+
+    /// Exchange a pane with its neighbour on the given side; focus stays with it.
+    pub fn swap_with_neighbour(&self, target: PaneId, direction: Direction) -> bool {
+        self.sync_ratios();
+        let swapped = {
+            let mut tree = self.inner.tree.borrow_mut();
+            match tree.neighbour(target, direction) {
+                Some(other) => tree.swap(target, other),
+                None => false,
+            }
+        };
+        if swapped {
+            self.render();
+            self.focus(target);
+        }
+        swapped
+    }
+
+    /// Grow (negative `delta`: shrink) a pane within its enclosing split of the given orientation.
+    pub fn resize(&self, target: PaneId, orientation: gtk::Orientation, delta: f64) -> bool {
+        self.sync_ratios();
+        let resized =
+            self.inner
+                .tree
+                .borrow_mut()
+                .resize(target, split_type_of(orientation), delta);
+        if resized {
+            // The shape didn't change, so move the existing dividers
+            let tree = self.inner.tree.borrow();
+            for (path, chain) in self.inner.paneds.borrow().iter() {
+                if let Some(ratios) = tree.ratios_at(path) {
+                    for (paned, fraction) in chain.iter().zip(divider_fractions(ratios)) {
+                        apply_ratio(paned, fraction);
+                    }
+                }
+            }
+        }
+        resized
+    }
+
+    pub fn focus(&self, id: PaneId) {
+        if let Some(terminal) = self
+            .container(id)
+            .and_then(|c| find_terminal_in_container(&c))
+        {
+            terminal.grab_focus();
+        }
+    }
+
+    pub fn focus_first(&self) {
+        let first = self.inner.tree.borrow().pane_ids().first().copied();
+        if let Some(id) = first {
+            self.focus(id);
+        }
+    }
+
+    /// Focus the pane that had focus last, or the first one.
+    pub fn focus_last(&self) {
+        let last = self
+            .focused_pane()
+            .and_then(|uuid| self.pane_with_uuid(&uuid));
+        match last {
+            Some(id) => self.focus(id),
+            None => self.focus_first(),
+        }
+    }
+
+    pub fn focused_pane(&self) -> Option<String> {
+        self.inner.focused.borrow().clone()
+    }
+
+    pub fn set_focused_pane(&self, uuid: Option<String>) {
+        *self.inner.focused.borrow_mut() = uuid;
+    }
+
+    pub fn pane_with_uuid(&self, uuid: &str) -> Option<PaneId> {
+        self.inner
+            .panes
+            .borrow()
+            .iter()
+            .find(|(_, container)| pane_uuid(container).as_deref() == Some(uuid))
+            .map(|(id, _)| *id)
+    }
+
+    fn add_container(&self, id: PaneId, pane: &TerminalLayout) {
+        // Ids must stay unique, also when the same layout is opened twice
+        let mut pane = pane.clone();
+        let taken = |uuid: &str| {
+            !glib::uuid_string_is_valid(uuid)
+                || self.pane_with_uuid(uuid).is_some()
+                || all_pane_containers()
+                    .iter()
+                    .any(|c| pane_uuid(c).as_deref() == Some(uuid))
+        };
+        if pane.id.as_deref().is_none_or(taken) {
+            pane.id = Some(new_pane_uuid());
+        }
+
+        let container = create_terminal_with_title(&pane);
+        unsafe {
+            container.set_data("pane_id", id);
+        }
+
+        // Remember the focused pane so it gets focus back after a tab switch or restore
+        if let Some(terminal) = find_terminal_in_container(&container) {
+            let focus = gtk::EventControllerFocus::new();
+            focus.connect_enter(|controller| {
+                let Some(terminal) = controller.widget() else {
+                    return;
+                };
+                if let (Some(workspace), Some(container)) = (
+                    Workspace::for_widget(&terminal),
+                    pane_container_of(&terminal),
+                ) {
+                    workspace.set_focused_pane(pane_uuid(&container));
+                }
+            });
+            terminal.add_controller(focus);
+        }
+        self.inner.panes.borrow_mut().insert(id, container);
+    }
+
+    // Rebuild the Paned hierarchy from the model, reusing the pane containers
+    fn render(&self) {
+        for (_, chain) in self.inner.paneds.borrow_mut().drain(..) {
+            for paned in chain {
+                paned.set_start_child(gtk::Widget::NONE);
+                paned.set_end_child(gtk::Widget::NONE);
+            }
+        }
+        while let Some(child) = self.root.first_child() {
+            self.root.remove(&child);
+        }
+
+        let tree = self.inner.tree.borrow();
+        if let Some(node) = tree.root() {
+            let widget = self.build_node(node, &mut Vec::new());
+            self.root.append(&widget);
+        }
+    }
+
+    fn build_node(&self, node: &LayoutNode, path: &mut Vec<usize>) -> gtk::Widget {
+        match node {
+            LayoutNode::Pane { id, .. } => self
+                .container(*id)
+                .expect("Pane container should exist")
+                .upcast(),
+            LayoutNode::Split {
+                split_type,
+                children,
+                ratios,
+            } => {
+                let widgets: Vec<gtk::Widget> = children
+                    .iter()
+                    .enumerate()
+                    .map(|(i, child)| {
+                        path.push(i);
+                        let widget = self.build_node(child, path);
+                        path.pop();
+                        widget
+                    })
+                    .collect();
+
+                // N children become a chain of N-1 Paned: [c0 | [c1 | [c2 | c3]]]
+                let orientation = orientation_of(*split_type);
+                let mut chain = Vec::new();
+                let mut end = widgets.last().expect("Split has children").clone();
+                let fractions = divider_fractions(ratios);
+                for i in (0..widgets.len() - 1).rev() {
+                    let paned = gtk::Paned::new(orientation);
+                    paned.set_hexpand(true);
+                    paned.set_vexpand(true);
+                    paned.set_wide_handle(true);
+                    paned.set_start_child(Some(&widgets[i]));
+                    paned.set_end_child(Some(&end));
+
+                    apply_ratio(&paned, fractions[i]);
+
+                    end = paned.clone().upcast();
+                    chain.insert(0, paned);
+                }
+
+                self.inner.paneds.borrow_mut().push((path.clone(), chain));
+                end
+            }
+        }
+    }
+
+    // Dividers may have been dragged since the last render; capture them as a
+    // fraction of the allocated size so they survive window resizes and reloads
+    fn sync_ratios(&self) {
+        let mut tree = self.inner.tree.borrow_mut();
+        for (path, chain) in self.inner.paneds.borrow().iter() {
+            if chain.iter().any(|p| paned_size(p) <= 0) {
+                continue;
+            }
+
+            let mut ratios = Vec::with_capacity(chain.len() + 1);
+            let mut remaining = 1.0;
+            for paned in chain {
+                let fraction = (paned.position() as f64 / paned_size(paned) as f64).clamp(0.0, 1.0);
+                // Three decimals are plenty and keep hand-edited layout files readable
+                let ratio = ((remaining * fraction) * 1000.0).round() / 1000.0;
+                ratios.push(ratio);
+                remaining -= ratio;
+            }
+            ratios.push(((remaining * 1000.0).round() / 1000.0).max(0.0));
+            tree.set_ratios(path, ratios);
+        }
+    }
+}
+
+/// Stable id of the pane, as saved in layouts and given to its shell.
+pub fn pane_uuid(container: &gtk::Box) -> Option<String> {
+    unsafe {
+        container
+            .data::<String>("pane_uuid")
+            .map(|id| id.as_ref().clone())
+    }
+}
+
+/// Every pane container in every window of the application.
+pub fn all_pane_containers() -> Vec<gtk::Box> {
+    let Some(app) = gio::Application::default().and_downcast::<gtk::Application>() else {
+        return Vec::new();
+    };
+    app.windows()
+        .iter()
+        .filter_map(|w| w.clone().downcast::<gtk::ApplicationWindow>().ok())
+        .filter_map(|w| window_notebook(&w))
+        .flat_map(|notebook| workspaces(&notebook))
+        .flat_map(|workspace| workspace.containers())
+        .collect()
+}
+
+pub fn pane_id_of(container: &gtk::Box) -> Option<PaneId> {
+    unsafe { container.data::<PaneId>("pane_id").map(|p| *p.as_ref()) }
+}
+
+/// The pane container a widget (usually a terminal) lives in.
+pub fn pane_container_of(widget: &impl IsA<gtk::Widget>) -> Option<gtk::Box> {
+    let mut current = Some(widget.clone().upcast::<gtk::Widget>());
+    while let Some(widget) = current {
+        if let Ok(container) = widget.clone().downcast::<gtk::Box>() {
+            if pane_id_of(&container).is_some() {
+                return Some(container);
+            }
+        }
+        current = widget.parent();
+    }
+    None
+}
+
+// Position the divider once the Paned has been allocated a size
+fn apply_ratio(paned: &gtk::Paned, ratio: f64) {
+    paned.add_tick_callback(move |p, _| {
+        let size = paned_size(p);
+        if size > 0 {
+            p.set_position((size as f64 * ratio).round() as i32);
+            glib::ControlFlow::Break
+        } else {
+            glib::ControlFlow::Continue
+        }
+    });
+}
+
+fn paned_size(paned: &gtk::Paned) -> i32 {
+    match paned.orientation() {
+        gtk::Orientation::Vertical => paned.height(),
+        _ => paned.width(),
+    }
+}
+
+pub fn split_type_of(orientation: gtk::Orientation) -> SplitType {
+    match orientation {
+        gtk::Orientation::Vertical => SplitType::Vertical,
+        _ => SplitType::Horizontal,
+    }
+}
+
+pub fn orientation_of(split_type: SplitType) -> gtk::Orientation {
+    match split_type {
+        SplitType::Horizontal => gtk::Orientation::Horizontal,
+        SplitType::Vertical => gtk::Orientation::Vertical,
+    }
+}
@@ -1,6 +1,8 @@
 use gtk::prelude::*;
 use gtk4 as gtk;
 
+// MessageDialog is deprecated since GTK 4.10
+#[allow(deprecated)]
 pub fn show_error_dialog(title: &str, message: &str) {
     let dialog = gtk::MessageDialog::builder()
         .message_type(gtk::MessageType::Error)
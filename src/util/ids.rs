@@ -1,7 +1,22 @@
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 static TERMINAL_COUNTER: AtomicUsize = AtomicUsize::new(1);
+static PANE_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
-pub fn next_terminal_number() -> usize {
-    TERMINAL_COUNTER.fetch_add(1, Ordering::Relaxed)
+/// Next "Terminal N" number, skipping those `in_use` says are taken (e.g. by a loaded layout).
+pub fn next_terminal_number(in_use: impl Fn(usize) -> bool) -> usize {
+    loop {
+        let number = TERMINAL_COUNTER.fetch_add(1, Ordering::Relaxed);
+        if !in_use(number) {
+            return number;
+        }
+    }
+}
+
+pub fn new_pane_uuid() -> String {
+    glib::uuid_string_random().to_string()
+}
+
+pub fn next_pane_id() -> usize {
+    PANE_COUNTER.fetch_add(1, Ordering::Relaxed)
 }
@@ -1,2 +1,5 @@
 pub mod errors;
 pub mod ids;
+pub mod shell;
+pub mod spawn;
+pub mod zoom;
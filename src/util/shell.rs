@@ -0,0 +1,116 @@
+use std::path::Path;
+
+// Prompt and host-tool wrappers for bash inside the Flatpak sandbox
+const FLATPAK_RCFILE: &str = "/app/etc/spliterma-rc";
+
+// Used when nothing else names a shell that exists
+const FALLBACK_SHELLS: &[&str] = &["/bin/bash", "/bin/sh"];
+
+/// Argument vector of the interactive shell for new panes.
+pub fn shell_argv(configured: Option<&str>) -> Vec<String> {
+    if let Some(shell) = configured.filter(|s| !is_executable(s)) {
+        eprintln!("Configured shell {shell} is not executable, falling back");
+    }
+    let env_shell = std::env::var("SHELL").ok();
+    let passwd_shell = std::fs::read_to_string("/etc/passwd")
+        .ok()
+        .and_then(|passwd| passwd_shell(&passwd, &glib::user_name().to_string_lossy()));
+
+    let shell = resolve_shell(
+        [configured, env_shell.as_deref(), passwd_shell.as_deref()],
+        is_executable,
+    );
+    let rcfile = (in_flatpak() && Path::new(FLATPAK_RCFILE).exists()).then_some(FLATPAK_RCFILE);
+    with_rcfile(shell, rcfile)
+}
+
+/// First candidate that exists, in order of preference.
+pub fn resolve_shell<const N: usize>(
+    candidates: [Option<&str>; N],
+    exists: impl Fn(&str) -> bool,
+) -> String {
+    candidates
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .chain(FALLBACK_SHELLS.iter().copied())
+        .find(|shell| !shell.is_empty() && exists(shell))
+        .unwrap_or("/bin/sh")
+        .to_string()
+}
+
+/// The bundled rcfile only makes sense for bash.
+pub fn with_rcfile(shell: String, rcfile: Option<&str>) -> Vec<String> {
+    let is_bash = Path::new(&shell)
+        .file_name()
+        .is_some_and(|name| name == "bash");
+    match rcfile {
+        Some(rcfile) if is_bash => vec![shell, "--rcfile".to_string(), rcfile.to_string()],
+        _ => vec![shell],
+    }
+}
+
+/// Login shell of `user` in an /etc/passwd style file.
+pub fn passwd_shell(passwd: &str, user: &str) -> Option<String> {
+    passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() >= 7 && fields[0] == user)
+        .map(|fields| fields[6].trim().to_string())
+        .filter(|shell| !shell.is_empty())
+}
+
+pub fn in_flatpak() -> bool {
+    Path::new("/.flatpak-info").exists()
+}
+
+fn is_executable(path: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_existing_candidate_wins() {
+        let exists = |s: &str| s != "/usr/bin/fish";
+        assert_eq!(
+            resolve_shell([Some("/usr/bin/fish"), Some("/bin/zsh")], exists),
+            "/bin/zsh"
+        );
+        assert_eq!(
+            resolve_shell([None, Some(""), Some("/bin/zsh")], exists),
+            "/bin/zsh"
+        );
+        assert_eq!(resolve_shell([None, None], |_| false), "/bin/sh");
+        assert_eq!(resolve_shell([None], |s| s == "/bin/bash"), "/bin/bash");
+    }
+
+    #[test]
+    fn rcfile_only_for_bash() {
+        assert_eq!(
+            with_rcfile("/bin/bash".into(), Some(FLATPAK_RCFILE)),
+            vec!["/bin/bash", "--rcfile", FLATPAK_RCFILE]
+        );
+        assert_eq!(
+            with_rcfile("/usr/bin/zsh".into(), Some(FLATPAK_RCFILE)),
+            vec!["/usr/bin/zsh"]
+        );
+        assert_eq!(with_rcfile("/bin/bash".into(), None), vec!["/bin/bash"]);
+    }
+
+    #[test]
+    fn passwd_lookup() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\n\
+                      dana:x:1000:1000:Dana:/home/dana:/usr/bin/fish\n\
+                      svc:x:999:999::/:\n";
+        assert_eq!(
+            passwd_shell(passwd, "dana").as_deref(),
+            Some("/usr/bin/fish")
+        );
+        assert_eq!(passwd_shell(passwd, "svc"), None);
+        assert_eq!(passwd_shell(passwd, "nobody"), None);
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+/// Argument vector for a pane: the shell, or `command` (optionally followed by the shell).
+pub fn spawn_argv(shell: Vec<String>, command: Option<&[String]>, keep_shell: bool) -> Vec<String> {
+    let Some(command) = command.filter(|c| !c.is_empty()) else {
+        return shell;
+    };
+    if !keep_shell {
+        return command.to_vec();
+    }
+
+    // sh -c '"$@"; exec <shell>' spliterma <command...>
+    let exec_shell = shell
+        .iter()
+        .map(|s| shell_quote(s))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut argv = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        format!("\"$@\"; exec {exec_shell}"),
+        "spliterma".to_string(),
+    ];
+    argv.extend(command.iter().cloned());
+    argv
+}
+
+/// `KEY=VALUE` entries added on top of the inherited environment.
+pub fn spawn_env(env: &BTreeMap<String, String>) -> Vec<String> {
+    env.iter().map(|(k, v)| format!("{k}={v}")).collect()
+}
+
+pub fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c))
+    {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASH: &[&str] = &["/bin/bash", "--rcfile", "/app/etc/spliterma-rc"];
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn no_command_runs_the_shell() {
+        assert_eq!(spawn_argv(argv(BASH), None, false), argv(BASH));
+        assert_eq!(spawn_argv(argv(BASH), Some(&[]), true), argv(BASH));
+    }
+
+    #[test]
+    fn command_runs_directly_or_before_the_shell() {
+        let cmd = argv(&["tail", "-f", "/var/log/app log"]);
+        assert_eq!(spawn_argv(argv(BASH), Some(&cmd), false), cmd);
+
+        let wrapped = spawn_argv(argv(BASH), Some(&cmd), true);
+        assert_eq!(wrapped[..2], argv(&["/bin/sh", "-c"]));
+        assert_eq!(
+            wrapped[2],
+            "\"$@\"; exec /bin/bash --rcfile /app/etc/spliterma-rc"
+        );
+        assert_eq!(wrapped[4..], cmd);
+
+        let wrapped = spawn_argv(argv(&["/usr/bin/fish"]), Some(&cmd), true);
+        assert_eq!(wrapped[2], "\"$@\"; exec /usr/bin/fish");
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(shell_quote("/bin/bash"), "/bin/bash");
+        assert_eq!(shell_quote("it's here"), "'it'\\''s here'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn env_entries() {
+        let env = BTreeMap::from([
+            ("PORT".to_string(), "8080".to_string()),
+            ("MODE".to_string(), "dev".to_string()),
+        ]);
+        assert_eq!(spawn_env(&env), argv(&["MODE=dev", "PORT=8080"]));
+    }
+}
@@ -0,0 +1,41 @@
+// Font scale steps, as in other GNOME terminals
+const FONT_SCALES: &[f64] = &[
+    0.33, 0.5, 0.67, 0.8, 0.9, 1.0, 1.1, 1.2, 1.33, 1.5, 1.7, 2.0, 2.4, 2.9, 3.5, 4.0,
+];
+
+/// Next step up or down from `current`; stays put at either end.
+pub fn step_font_scale(current: f64, zoom_in: bool) -> f64 {
+    let current = clamp_font_scale(current);
+    let next = if zoom_in {
+        FONT_SCALES.iter().find(|s| **s > current + 0.001)
+    } else {
+        FONT_SCALES.iter().rev().find(|s| **s < current - 0.001)
+    };
+    next.copied().unwrap_or(current)
+}
+
+/// Keep a scale from a layout file within what the zoom steps allow.
+pub fn clamp_font_scale(scale: f64) -> f64 {
+    if !scale.is_finite() {
+        return 1.0;
+    }
+    scale.clamp(FONT_SCALES[0], FONT_SCALES[FONT_SCALES.len() - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_steps() {
+        assert_eq!(step_font_scale(1.0, true), 1.1);
+        assert_eq!(step_font_scale(1.0, false), 0.9);
+        // Off-step scales snap to the neighbouring step
+        assert_eq!(step_font_scale(1.05, true), 1.1);
+        assert_eq!(step_font_scale(1.05, false), 1.0);
+        assert_eq!(step_font_scale(4.0, true), 4.0);
+        assert_eq!(step_font_scale(0.33, false), 0.33);
+        assert_eq!(clamp_font_scale(f64::NAN), 1.0);
+        assert_eq!(clamp_font_scale(100.0), 4.0);
+    }
+}